@@ -0,0 +1,56 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use tiny_rusty_raytracer::{render_framebuffer, Camera, Ray, Rng, SceneDescription, Vector3};
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 48;
+
+fn camera() -> Camera {
+    Camera::new(
+        Vector3::new_zero(),
+        std::f64::consts::PI / 2.0,
+        WIDTH,
+        HEIGHT,
+    )
+}
+
+/// One primary ray through the center of every pixel.
+fn primary_rays(camera: &Camera) -> Vec<Ray> {
+    (0..HEIGHT)
+        .flat_map(|j| (0..WIDTH).map(move |i| (i, j)))
+        .map(|(i, j)| camera.ray_for_pixel(i, j))
+        .collect()
+}
+
+fn bench_render(c: &mut Criterion) {
+    let scene = SceneDescription::default().to_scene();
+    let camera = camera();
+    let rays = primary_rays(&camera);
+
+    let mut group = c.benchmark_group("rays");
+    // criterion reports throughput in elements per second, here rays/sec
+    group.throughput(Throughput::Elements(rays.len() as u64));
+    group.bench_function("intersect", |b| {
+        b.iter(|| {
+            for ray in &rays {
+                black_box(scene.intersect(black_box(ray)));
+            }
+        })
+    });
+    group.bench_function("trace", |b| {
+        let mut rng = Rng::new(0);
+        b.iter(|| {
+            for ray in &rays {
+                black_box(scene.trace(black_box(ray), 0, &mut rng));
+            }
+        })
+    });
+    group.bench_function("render_framebuffer", |b| {
+        b.iter(|| render_framebuffer(black_box(&scene), black_box(&camera)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_render);
+criterion_main!(benches);
@@ -0,0 +1,123 @@
+use crate::{Ray, Vector3};
+
+/// An axis-aligned bounding box spanning `min` to `max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3, max: Vector3) -> Aabb {
+        Aabb { min, max }
+    }
+
+    /// The smallest box containing both `self` and `other`.
+    pub fn surrounding(&self, other: &Aabb) -> Aabb {
+        Aabb::new(self.min.min(&other.min), self.max.max(&other.max))
+    }
+
+    /// Whether `ray` passes through the box between distances `t_min` and
+    /// `t_max`, using the slab method.
+    pub fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.intersect(ray, t_min, t_max).is_some()
+    }
+
+    /// Distances along `ray`, limited to [`t_min`, `t_max`], at which it
+    /// enters and leaves the box, if it passes through at all. Boxes flat
+    /// along an axis, as for axis-aligned planar objects, can still be hit.
+    pub fn intersect(&self, ray: &Ray, mut t_min: f64, mut t_max: f64) -> Option<(f64, f64)> {
+        let slabs = [
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z),
+        ];
+        for (origin, direction, min, max) in slabs {
+            // a ray parallel to the slab either never enters it or never
+            // leaves it; the general case below would give 0 * inf = NaN for
+            // one starting right on a boundary
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+            let inv_d = 1.0 / direction;
+            let mut t0 = (min - origin) * inv_d;
+            let mut t1 = (max - origin) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Vector3::new(-1.0, -1.0, -1.0), Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn surrounding_box_contains_both() {
+        let a = Aabb::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 1.0, 1.0));
+        let b = Aabb::new(Vector3::new(-1.0, 2.0, 0.5), Vector3::new(0.5, 3.0, 0.7));
+        assert_eq!(
+            a.surrounding(&b),
+            Aabb::new(Vector3::new(-1.0, 0.0, 0.0), Vector3::new(1.0, 3.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn ray_through_box() {
+        let ray = Ray::new(Vector3::new(0.5, 0.5, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(unit_box().hit(&ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn diagonal_ray_through_box() {
+        let ray = Ray::new(Vector3::new(-5.0, -5.0, -5.0), Vector3::new(1.0, 1.0, 1.0));
+        assert!(unit_box().hit(&ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn ray_missing_to_the_side() {
+        let ray = Ray::new(Vector3::new(2.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(!unit_box().hit(&ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn ray_pointing_away() {
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, 1.0));
+        assert!(!unit_box().hit(&ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn flat_box_is_hit() {
+        let flat = Aabb::new(Vector3::new(-1.0, -1.0, -5.0), Vector3::new(1.0, 1.0, -5.0));
+        let ray = Ray::new(Vector3::new(0.5, 0.5, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(flat.intersect(&ray, 0.0, f64::INFINITY), Some((5.0, 5.0)));
+    }
+
+    #[test]
+    fn ray_parallel_to_slab_on_its_boundary() {
+        let ray = Ray::new(Vector3::new(1.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(unit_box().hit(&ray, 0.0, f64::INFINITY));
+        let ray = Ray::new(Vector3::new(1.0, 1.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(unit_box().hit(&ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn box_beyond_t_max_is_missed() {
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(!unit_box().hit(&ray, 0.0, 3.0));
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{background, EnvironmentMap, Vector3, BACKGROUND_BOTTOM, BACKGROUND_TOP};
+
+/// What rays that miss every object see.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Background {
+    Solid(Vector3),
+    /// Vertical gradient from `bottom`, straight down, to `top`, straight up.
+    Gradient {
+        top: Vector3,
+        bottom: Vector3,
+    },
+    Environment(EnvironmentMap),
+}
+
+/// The default sky gradient, for tracers without a scene of their own.
+pub(crate) static DEFAULT_BACKGROUND: Background = Background::Gradient {
+    top: BACKGROUND_TOP,
+    bottom: BACKGROUND_BOTTOM,
+};
+
+impl Background {
+    /// Color seen looking along `dir`, which must be unit-length.
+    pub fn color(&self, dir: &Vector3) -> Vector3 {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient { top, bottom } => background(dir, *top, *bottom),
+            Background::Environment(environment) => environment.sample(dir),
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Background {
+        DEFAULT_BACKGROUND.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_the_sky_gradient() {
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(Background::default().color(&up), BACKGROUND_TOP);
+        assert_eq!(Background::default().color(&-up), BACKGROUND_BOTTOM);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Aabb, HitRecord, Hittable, Material, Ray, Vector3};
+
+/// A solid, axis-aligned box between the corners `min` and `max`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxPrimitive {
+    pub min: Vector3,
+    pub max: Vector3,
+    pub material: Material,
+}
+
+impl BoxPrimitive {
+    pub fn new(min: Vector3, max: Vector3, material: Material) -> BoxPrimitive {
+        BoxPrimitive { min, max, material }
+    }
+
+    /// Outward normal of the face that `point`, on the surface, lies on.
+    fn normal_at(&self, point: &Vector3) -> Vector3 {
+        // the face is on the axis where the point is furthest out relative
+        // to the box's half-size
+        let center = (self.min + self.max) * 0.5;
+        let half = (self.max - self.min) * 0.5;
+        let p = *point - center;
+        let scaled = [p.x / half.x, p.y / half.y, p.z / half.z];
+        let axis = (0..3)
+            .max_by(|&a, &b| scaled[a].abs().total_cmp(&scaled[b].abs()))
+            .unwrap();
+        let mut normal = [0.0; 3];
+        normal[axis] = scaled[axis].signum();
+        Vector3::from(normal)
+    }
+}
+
+impl Hittable for BoxPrimitive {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        let (enter, leave) =
+            Aabb::new(self.min, self.max).intersect(ray, f64::NEG_INFINITY, f64::INFINITY)?;
+        // a ray starting inside the box hits it on the way out
+        let t = if enter >= 0.0 {
+            enter
+        } else if leave >= 0.0 {
+            leave
+        } else {
+            return None;
+        };
+        let point = ray.at(t);
+        let (front_face, normal) = HitRecord::face_normal(ray, self.normal_at(&point));
+        Some(HitRecord {
+            t,
+            point,
+            normal,
+            front_face,
+            object_id: None,
+            material: self.material.clone(),
+            u: 0.0,
+            v: 0.0,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.min, self.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> BoxPrimitive {
+        BoxPrimitive::new(
+            Vector3::new(-1.0, -1.0, -1.0),
+            Vector3::new(1.0, 1.0, 1.0),
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn each_face_has_its_outward_normal() {
+        let axes = [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ];
+        for axis in axes {
+            for normal in [axis, -axis] {
+                // come at the face from outside, slightly off its center
+                let offset = Vector3::new(0.1, 0.2, 0.3);
+                let origin = normal * 5.0 + offset - normal * (offset * normal);
+                let hit = unit_box()
+                    .ray_intersect(&Ray::new(origin, -normal))
+                    .unwrap();
+                assert_eq!(hit.normal, normal);
+                assert!((hit.t - 4.0).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn ray_from_inside_hits_far_face() {
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let hit = unit_box().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn box_behind_ray_is_missed() {
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(unit_box().ray_intersect(&ray), None);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{Aabb, HitRecord, Hittable, Ray, Timer, Vector3};
+
+/// A bounding volume hierarchy: a binary tree of objects in which a ray only
+/// visits the subtrees whose bounding boxes it passes through.
+pub struct BvhNode {
+    /// `None` for nodes holding objects without a bounding box, such as
+    /// infinite planes; those are always visited.
+    bbox: Option<Aabb>,
+    contents: Contents,
+}
+
+enum Contents {
+    Empty,
+    Leaf(Box<dyn Hittable>),
+    Split(Box<BvhNode>, Box<BvhNode>),
+}
+
+impl BvhNode {
+    /// Builds the tree by recursively splitting the objects at the median of
+    /// their bounding box centers along the longest axis. How long that took
+    /// is logged at info level.
+    pub fn build(objects: Vec<Box<dyn Hittable>>) -> BvhNode {
+        let timer = Timer::start();
+        let count = objects.len();
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = objects
+            .into_iter()
+            .partition(|object| object.bounding_box().is_some());
+
+        let mut node = if bounded.is_empty() {
+            BvhNode {
+                bbox: None,
+                contents: Contents::Empty,
+            }
+        } else {
+            BvhNode::build_bounded(bounded)
+        };
+        for object in unbounded {
+            node = BvhNode {
+                bbox: None,
+                contents: Contents::Split(Box::new(BvhNode::leaf(object)), Box::new(node)),
+            };
+        }
+        log::info!(
+            "built BVH over {} objects in {:.0} ms",
+            count,
+            timer.elapsed_ms()
+        );
+        node
+    }
+
+    fn leaf(object: Box<dyn Hittable>) -> BvhNode {
+        BvhNode {
+            bbox: object.bounding_box(),
+            contents: Contents::Leaf(object),
+        }
+    }
+
+    fn build_bounded(mut objects: Vec<Box<dyn Hittable>>) -> BvhNode {
+        if objects.len() == 1 {
+            return BvhNode::leaf(objects.pop().unwrap());
+        }
+
+        let centroids = objects
+            .iter()
+            .map(|object| {
+                let c = centroid(object.as_ref());
+                Aabb::new(c, c)
+            })
+            .reduce(|a, b| a.surrounding(&b))
+            .unwrap();
+        let extent = centroids.max - centroids.min;
+        let axis: fn(&Vector3) -> f64 = if extent.x >= extent.y && extent.x >= extent.z {
+            |v| v.x
+        } else if extent.y >= extent.z {
+            |v| v.y
+        } else {
+            |v| v.z
+        };
+        objects.sort_by(|a, b| axis(&centroid(a.as_ref())).total_cmp(&axis(&centroid(b.as_ref()))));
+
+        let right = objects.split_off(objects.len() / 2);
+        let left = BvhNode::build_bounded(objects);
+        let right = BvhNode::build_bounded(right);
+        BvhNode {
+            bbox: Some(left.bbox.unwrap().surrounding(&right.bbox.unwrap())),
+            contents: Contents::Split(Box::new(left), Box::new(right)),
+        }
+    }
+}
+
+fn centroid(object: &dyn Hittable) -> Vector3 {
+    let bbox = object.bounding_box().unwrap();
+    (bbox.min + bbox.max) * 0.5
+}
+
+impl Hittable for BvhNode {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        if let Some(bbox) = self.bbox {
+            if !bbox.hit(ray, 0.0, f64::INFINITY) {
+                return None;
+            }
+        }
+        match &self.contents {
+            Contents::Empty => None,
+            Contents::Leaf(object) => object.ray_intersect(ray),
+            Contents::Split(left, right) => {
+                match (left.ray_intersect(ray), right.ray_intersect(ray)) {
+                    (Some(l), Some(r)) => Some(if r.t < l.t { r } else { l }),
+                    (l, r) => l.or(r),
+                }
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::{scene_intersect, Disk, Material, Plane, Quad, Sphere, Triangle};
+
+    fn random_scene(rng: &mut StdRng) -> Vec<Box<dyn Hittable>> {
+        let mut objects: Vec<Box<dyn Hittable>> = (0..200)
+            .map(|_| {
+                let center = Vector3::new(
+                    rng.random_range(-20.0..20.0),
+                    rng.random_range(-20.0..20.0),
+                    rng.random_range(-60.0..-10.0),
+                );
+                let color = Vector3::new(rng.random(), rng.random(), rng.random());
+                Box::new(Sphere::new(
+                    center,
+                    rng.random_range(0.2..2.0),
+                    Material::new(color),
+                )) as Box<dyn Hittable>
+            })
+            .collect();
+        // flat objects facing the rays, whose boxes have no depth along z
+        for _ in 0..30 {
+            let corner = Vector3::new(
+                rng.random_range(-20.0..20.0),
+                rng.random_range(-20.0..20.0),
+                rng.random_range(-60.0..-10.0),
+            );
+            let material = Material::new(Vector3::new(rng.random(), rng.random(), rng.random()));
+            let (x, y) = (Vector3::new(1.5, 0.0, 0.0), Vector3::new(0.0, 1.5, 0.0));
+            objects.push(Box::new(Quad::new(corner, x, y, material.clone())));
+            objects.push(Box::new(Disk::new(
+                corner + Vector3::new(3.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 1.0),
+                1.0,
+                material.clone(),
+            )));
+            objects.push(Box::new(Triangle::new(
+                corner - x,
+                corner - y,
+                corner - x - y,
+                material,
+            )));
+        }
+        objects.push(Box::new(Plane::new(
+            Vector3::new(0.0, -25.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Material::new(Vector3::new(0.5, 0.5, 0.5)),
+        )));
+        objects
+    }
+
+    #[test]
+    fn bvh_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let objects = random_scene(&mut rng);
+        let bvh = BvhNode::build(random_scene(&mut StdRng::seed_from_u64(42)));
+
+        for _ in 0..1000 {
+            let direction = Vector3::new(
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                -1.0,
+            );
+            let ray = Ray::new(Vector3::new_zero(), direction);
+            // the tree doesn't number its objects
+            let expected = scene_intersect(&ray, &objects, f64::INFINITY).map(|hit| HitRecord {
+                object_id: None,
+                ..hit
+            });
+            let actual = bvh.ray_intersect(&ray);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn empty_bvh_is_never_hit() {
+        let bvh = BvhNode::build(vec![]);
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(bvh.ray_intersect(&ray), None);
+    }
+}
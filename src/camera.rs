@@ -0,0 +1,412 @@
+use crate::{Ray, Rng, Vector3};
+
+/// How the camera maps pixels to rays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Rays fan out from the camera position; `fov` is the vertical field of
+    /// view, in radians.
+    Perspective { fov: f64 },
+    /// Rays all run along the view direction, leaving from a window `scale`
+    /// world units high centered on the camera position.
+    Orthographic { scale: f64 },
+}
+
+/// Settings for sampling each pixel until its color settles instead of a
+/// fixed number of times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    /// Rays cast before checking whether the pixel has converged; at least 2
+    /// are always cast.
+    pub min_samples: usize,
+    /// Rays cast at most, converged or not.
+    pub max_samples: usize,
+    /// The pixel has converged once the variance of its mean color, in every
+    /// channel, is at most this.
+    pub variance_threshold: f64,
+}
+
+/// A pinhole camera, or a thin-lens one with depth of field when its
+/// `aperture` is open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    pub position: Vector3,
+    pub projection: Projection,
+    pub width: usize,
+    pub height: usize,
+    /// Number of jittered rays averaged per pixel; 1 casts a single ray
+    /// through the pixel center.
+    pub samples_per_pixel: usize,
+    /// When set, pixels are sampled adaptively and `samples_per_pixel` is
+    /// ignored.
+    pub adaptive: Option<AdaptiveSampling>,
+    /// Highest luminance a single sample may have; brighter ones are scaled
+    /// down, keeping their hue, before being averaged. This removes the
+    /// bright specks ("fireflies") that rare, very bright paths leave in
+    /// path-traced images, at the cost of darkening them slightly. `None`,
+    /// the default, keeps samples as they are.
+    pub firefly_clamp: Option<f64>,
+    /// Seeds the jitter; renders with the same seed are identical.
+    pub seed: u64,
+    /// Diameter of the lens; 0 keeps everything in focus like a pinhole.
+    pub aperture: f64,
+    /// Distance along the view direction of the plane that is in focus.
+    pub focus_distance: f64,
+    // orthonormal basis: image right, image up, and the opposite of the
+    // viewing direction
+    u: Vector3,
+    v: Vector3,
+    w: Vector3,
+}
+
+impl Camera {
+    /// A perspective camera looking down -Z with +Y up.
+    pub fn new(position: Vector3, fov: f64, width: usize, height: usize) -> Camera {
+        Camera::look_at(
+            position,
+            position + Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            fov,
+            width,
+            height,
+        )
+    }
+
+    /// A camera at `position` aimed at `target`, rolled so that `up` points
+    /// up in the image.
+    pub fn look_at(
+        position: Vector3,
+        target: Vector3,
+        up: Vector3,
+        fov: f64,
+        width: usize,
+        height: usize,
+    ) -> Camera {
+        let w = (position - target).normalize();
+        let u = up.cross(&w).normalize();
+        let v = w.cross(&u);
+        Camera {
+            position,
+            projection: Projection::Perspective { fov },
+            width,
+            height,
+            samples_per_pixel: 1,
+            adaptive: None,
+            firefly_clamp: None,
+            seed: 0,
+            aperture: 0.0,
+            focus_distance: 1.0,
+            u,
+            v,
+            w,
+        }
+    }
+
+    /// The primary ray through the center of pixel (`i`, `j`), counting from
+    /// the top-left corner of the image.
+    pub fn ray_for_pixel(&self, i: usize, j: usize) -> Ray {
+        self.ray_through(i, j, 0.5, 0.5)
+    }
+
+    /// The primary ray through pixel (`i`, `j`) at offset (`dx`, `dy`) from
+    /// its top-left corner, both in [0, 1).
+    pub fn ray_through(&self, i: usize, j: usize, dx: f64, dy: f64) -> Ray {
+        let (width, height) = (self.width as f64, self.height as f64);
+        // position on the image plane, from -1 to 1 along both axes
+        let x = 2.0 * (i as f64 + dx) / width - 1.0;
+        let y = -(2.0 * (j as f64 + dy) / height - 1.0);
+        match self.projection {
+            Projection::Perspective { fov } => {
+                let half = (fov / 2.0).tan();
+                let (x, y) = (x * half * width / height, y * half);
+                Ray::new(self.position, self.u * x + self.v * y - self.w)
+            }
+            Projection::Orthographic { scale } => {
+                let offset = (self.u * (x * width / height) + self.v * y) * (scale / 2.0);
+                Ray::new(self.position + offset, -self.w)
+            }
+        }
+    }
+
+    /// Like [`Camera::ray_through`], but leaving from a random point on the
+    /// lens and aimed so that rays for the same pixel offset meet on the
+    /// focal plane. With the aperture closed this is the pinhole ray and
+    /// `rng` is left untouched.
+    pub fn lens_ray(&self, i: usize, j: usize, dx: f64, dy: f64, rng: &mut Rng) -> Ray {
+        let pinhole = self.ray_through(i, j, dx, dy);
+        if self.aperture <= 0.0 {
+            return pinhole;
+        }
+        let focus = pinhole.at(self.focus_distance / -(pinhole.direction * self.w));
+        let (lx, ly) = rng.random_in_unit_disk();
+        let origin = pinhole.origin + (self.u * lx + self.v * ly) * (self.aperture / 2.0);
+        Ray::new(origin, focus - origin)
+    }
+
+    /// Color of pixel (`i`, `j`): the average of `shade` over
+    /// `samples_per_pixel` rays jittered randomly inside the pixel, or over
+    /// as many as it takes to converge when sampling adaptively. `shade`
+    /// gets `rng` too, for any random choices of its own.
+    pub fn sample_pixel<F>(&self, i: usize, j: usize, rng: &mut Rng, mut shade: F) -> Vector3
+    where
+        F: FnMut(&Ray, &mut Rng) -> Vector3,
+    {
+        let mut shade = |ray: &Ray, rng: &mut Rng| self.clamp_sample(shade(ray, rng));
+        if let Some(adaptive) = &self.adaptive {
+            return self.sample_adaptively(adaptive, i, j, rng, shade);
+        }
+        if self.samples_per_pixel <= 1 {
+            let ray = self.sample_ray(i, j, 0.5, 0.5, rng);
+            return shade(&ray, rng);
+        }
+        let mut color = Vector3::new_zero();
+        for sample in 0..self.samples_per_pixel {
+            let (dx, dy) = self.jitter(sample, rng);
+            let ray = self.sample_ray(i, j, dx, dy, rng);
+            color += shade(&ray, rng);
+        }
+        color / self.samples_per_pixel as f64
+    }
+
+    /// `color` scaled down to `firefly_clamp` luminance if it is brighter.
+    fn clamp_sample(&self, color: Vector3) -> Vector3 {
+        match self.firefly_clamp {
+            Some(max) if color.luminance() > max => color * (max / color.luminance()),
+            _ => color,
+        }
+    }
+
+    /// A [`Camera::lens_ray`] cast at a random moment while the shutter is
+    /// open, so that moving objects blur.
+    fn sample_ray(&self, i: usize, j: usize, dx: f64, dy: f64, rng: &mut Rng) -> Ray {
+        let ray = self.lens_ray(i, j, dx, dy, rng);
+        ray.with_time(rng.next_f64())
+    }
+
+    /// Samples pixel (`i`, `j`) at random offsets, keeping a running mean
+    /// and variance (Welford's method) until the mean is known well enough.
+    fn sample_adaptively<F>(
+        &self,
+        adaptive: &AdaptiveSampling,
+        i: usize,
+        j: usize,
+        rng: &mut Rng,
+        mut shade: F,
+    ) -> Vector3
+    where
+        F: FnMut(&Ray, &mut Rng) -> Vector3,
+    {
+        let min_samples = adaptive.min_samples.max(2);
+        let max_samples = adaptive.max_samples.max(min_samples);
+        let mut mean = Vector3::new_zero();
+        let mut squared_deviations = Vector3::new_zero();
+        for n in 1..=max_samples {
+            let (dx, dy) = (rng.next_f64(), rng.next_f64());
+            let ray = self.sample_ray(i, j, dx, dy, rng);
+            let color = shade(&ray, rng);
+            let delta = color - mean;
+            mean += delta / n as f64;
+            squared_deviations += delta.componentwise(&(color - mean));
+
+            let n = n as f64;
+            let variance_of_mean = squared_deviations / ((n - 1.0) * n);
+            if n >= min_samples as f64
+                && variance_of_mean.max_component() <= adaptive.variance_threshold
+            {
+                break;
+            }
+        }
+        mean
+    }
+
+    /// Offset inside the pixel of the `sample`th ray. When the sample count
+    /// is a perfect square the pixel is split into a grid with one jittered
+    /// sample per cell, which is less noisy than jittering over the whole
+    /// pixel.
+    fn jitter(&self, sample: usize, rng: &mut Rng) -> (f64, f64) {
+        let (dx, dy) = (rng.next_f64(), rng.next_f64());
+        let n = self.samples_per_pixel.isqrt();
+        if n * n != self.samples_per_pixel {
+            return (dx, dy);
+        }
+        let (cell_x, cell_y) = ((sample % n) as f64, (sample / n) as f64);
+        ((cell_x + dx) / n as f64, (cell_y + dy) / n as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn center_pixel_looks_down_negative_z() {
+        let camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 101, 101);
+        let ray = camera.ray_for_pixel(50, 50);
+        assert!(ray.direction.approx_eq(&Vector3::new(0.0, 0.0, -1.0), 1e-9));
+    }
+
+    #[test]
+    fn top_left_pixel_points_up_and_left() {
+        let camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 4, 3);
+        let ray = camera.ray_for_pixel(0, 0);
+        assert!(ray.direction.x < 0.0 && ray.direction.y > 0.0 && ray.direction.z < 0.0);
+    }
+
+    #[test]
+    fn default_look_at_matches_new() {
+        let fov = std::f64::consts::PI / 2.0;
+        let camera = Camera::new(Vector3::new_zero(), fov, 64, 48);
+        let looking = Camera::look_at(
+            Vector3::new_zero(),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            fov,
+            64,
+            48,
+        );
+        assert_eq!(looking.ray_for_pixel(32, 24), camera.ray_for_pixel(32, 24));
+        assert_eq!(looking.ray_for_pixel(3, 7), camera.ray_for_pixel(3, 7));
+    }
+
+    #[test]
+    fn look_at_rotates_view_direction() {
+        let camera = Camera::look_at(
+            Vector3::new(1.0, 2.0, 3.0),
+            Vector3::new(5.0, 2.0, 3.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            std::f64::consts::PI / 2.0,
+            101,
+            101,
+        );
+        let ray = camera.ray_for_pixel(50, 50);
+        assert_eq!(ray.origin, Vector3::new(1.0, 2.0, 3.0));
+        assert!(ray.direction.approx_eq(&Vector3::new(1.0, 0.0, 0.0), 1e-9));
+    }
+
+    #[test]
+    fn orthographic_rays_are_parallel() {
+        let mut camera = Camera::new(Vector3::new(0.0, 0.0, 1.0), 1.0, 4, 2);
+        camera.projection = Projection::Orthographic { scale: 2.0 };
+        let corner = camera.ray_for_pixel(0, 0);
+        let other = camera.ray_for_pixel(3, 1);
+        assert_eq!(corner.direction, Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(other.direction, corner.direction);
+        assert_eq!(corner.origin, Vector3::new(-1.5, 0.5, 1.0));
+        assert_eq!(other.origin, Vector3::new(1.5, -0.5, 1.0));
+    }
+
+    #[test]
+    fn closed_aperture_matches_pinhole() {
+        let camera = Camera::new(Vector3::new(1.0, 0.0, 2.0), 1.0, 64, 48);
+        let mut rng = Rng::new(3);
+        assert_eq!(
+            camera.lens_ray(10, 20, 0.5, 0.5, &mut rng),
+            camera.ray_for_pixel(10, 20)
+        );
+        assert_eq!(rng, Rng::new(3));
+    }
+
+    #[test]
+    fn lens_rays_meet_on_focal_plane() {
+        let mut camera = Camera::new(Vector3::new_zero(), 1.0, 64, 48);
+        camera.aperture = 0.5;
+        camera.focus_distance = 4.0;
+        let mut rng = Rng::new(11);
+        let pinhole = camera.ray_for_pixel(10, 20);
+        let focus = pinhole.at(4.0 / -pinhole.direction.z);
+        for _ in 0..8 {
+            let ray = camera.lens_ray(10, 20, 0.5, 0.5, &mut rng);
+            assert_ne!(ray.origin, camera.position);
+            let t = (focus.z - ray.origin.z) / ray.direction.z;
+            assert!(ray.at(t).approx_eq(&focus, 1e-9));
+        }
+    }
+
+    #[test]
+    fn square_sample_counts_are_stratified() {
+        let mut camera = Camera::new(Vector3::new_zero(), 1.0, 8, 8);
+        camera.samples_per_pixel = 4;
+        let mut rng = Rng::new(5);
+
+        let mut quadrants: Vec<(bool, bool)> = (0..4)
+            .map(|sample| {
+                let (dx, dy) = camera.jitter(sample, &mut rng);
+                (dx >= 0.5, dy >= 0.5)
+            })
+            .collect();
+        quadrants.sort();
+        assert_eq!(
+            quadrants,
+            vec![(false, false), (false, true), (true, false), (true, true)]
+        );
+    }
+
+    fn adaptive_camera(max_samples: usize) -> Camera {
+        let mut camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 6, 4);
+        camera.adaptive = Some(AdaptiveSampling {
+            min_samples: 4,
+            max_samples,
+            variance_threshold: 1e-4,
+        });
+        camera
+    }
+
+    #[test]
+    fn uniform_region_converges_at_min_samples() {
+        let camera = adaptive_camera(64);
+        let color = Vector3::new(0.2, 0.4, 0.6);
+        let mut rng = Rng::new(1);
+
+        for j in 0..camera.height {
+            for i in 0..camera.width {
+                let mut samples = 0;
+                let sampled = camera.sample_pixel(i, j, &mut rng, |_, _| {
+                    samples += 1;
+                    color
+                });
+                assert_eq!(samples, 4);
+                assert!(sampled.approx_eq(&color, 1e-12));
+            }
+        }
+    }
+
+    #[test]
+    fn noisy_pixels_take_more_samples() {
+        let camera = adaptive_camera(64);
+        let mut rng = Rng::new(2);
+
+        let mut samples = 0;
+        camera.sample_pixel(1, 1, &mut rng, |_, rng| {
+            samples += 1;
+            Vector3::new(1.0, 1.0, 1.0) * rng.next_f64()
+        });
+        assert_eq!(samples, 64);
+    }
+
+    #[test]
+    fn firefly_clamp_caps_only_bright_samples() {
+        let mut camera = Camera::new(Vector3::new_zero(), 1.0, 8, 8);
+        camera.samples_per_pixel = 2;
+        camera.firefly_clamp = Some(10.0);
+        let normal = Vector3::new(0.5, 0.5, 0.5);
+        let firefly = Vector3::new(1e6, 1e6, 1e6);
+        let mut rng = Rng::new(9);
+
+        let mut samples = [firefly, normal].into_iter();
+        let sampled = camera.sample_pixel(0, 0, &mut rng, |_, _| samples.next().unwrap());
+        assert!(sampled.approx_eq(&Vector3::new(5.25, 5.25, 5.25), 1e-9));
+
+        let sampled = camera.sample_pixel(0, 0, &mut rng, |_, _| normal);
+        assert_eq!(sampled, normal);
+    }
+
+    #[test]
+    fn jittered_samples_of_uniform_region_keep_its_color() {
+        let mut camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 8, 8);
+        camera.samples_per_pixel = 4;
+        let color = Vector3::new(0.2, 0.4, 0.6);
+        let mut rng = Rng::new(7);
+
+        let sampled = camera.sample_pixel(3, 5, &mut rng, |_, _| color);
+        assert!(sampled.approx_eq(&color, 1e-12));
+    }
+}
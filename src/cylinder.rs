@@ -0,0 +1,176 @@
+use crate::{Aabb, HitRecord, Hittable, Material, Ray, Vector3};
+
+/// A solid cylinder standing on the disk at `base`, extending `height`
+/// along `axis`, with both ends capped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cylinder {
+    pub base: Vector3,
+    pub axis: Vector3,
+    pub radius: f64,
+    pub height: f64,
+    pub material: Material,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: Vector3,
+        axis: Vector3,
+        radius: f64,
+        height: f64,
+        material: Material,
+    ) -> Cylinder {
+        Cylinder {
+            base,
+            axis: axis.normalize(),
+            radius,
+            height,
+            material,
+        }
+    }
+
+    fn top(&self) -> Vector3 {
+        self.base + self.axis * self.height
+    }
+
+    /// Distance to the nearest hit on either cap, and the outward normal
+    /// there.
+    fn cap_intersect(&self, ray: &Ray) -> Option<(f64, Vector3)> {
+        let denom = self.axis * ray.direction;
+        if denom.abs() < 1e-6 {
+            return None; // the ray runs parallel to the caps
+        }
+        [(self.base, -self.axis), (self.top(), self.axis)]
+            .into_iter()
+            .filter_map(|(center, outward)| {
+                let t = (center - ray.origin) * self.axis / denom;
+                let inside = ray.at(t).distance_squared(&center) <= self.radius * self.radius;
+                (t >= 0.0 && inside).then_some((t, outward))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    /// Distance to the nearest hit on the curved side, between the caps, and
+    /// the outward normal there.
+    fn side_intersect(&self, ray: &Ray) -> Option<(f64, Vector3)> {
+        // solve |p - base|^2 = r^2 with the components along the axis removed
+        let offset = ray.origin - self.base;
+        let d = ray.direction - self.axis * (ray.direction * self.axis);
+        let o = offset - self.axis * (offset * self.axis);
+        let a = d.length_squared();
+        if a < 1e-12 {
+            return None; // the ray runs parallel to the axis
+        }
+        let b = 2.0 * (d * o);
+        let c = o.length_squared() - self.radius * self.radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let sqrt = discriminant.sqrt();
+        [(-b - sqrt) / (2.0 * a), (-b + sqrt) / (2.0 * a)]
+            .into_iter()
+            .filter(|t| *t >= 0.0)
+            .find_map(|t| {
+                let point = ray.at(t);
+                let along = (point - self.base) * self.axis;
+                if !(0.0..=self.height).contains(&along) {
+                    return None;
+                }
+                let center = self.base + self.axis * along;
+                Some((t, (point - center).normalize()))
+            })
+    }
+}
+
+impl Hittable for Cylinder {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        // only the hit returned needs a copy of the material
+        let (t, outward) = [self.side_intersect(ray), self.cap_intersect(ray)]
+            .into_iter()
+            .flatten()
+            .min_by(|a, b| a.0.total_cmp(&b.0))?;
+        let (front_face, normal) = HitRecord::face_normal(ray, outward);
+        Some(HitRecord {
+            t,
+            point: ray.at(t),
+            normal,
+            front_face,
+            object_id: None,
+            material: self.material.clone(),
+            u: 0.0,
+            v: 0.0,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // the rims reach out by the radius along each axis, shrunk by how
+        // much the caps are tilted away from it
+        let a = self.axis;
+        let extent = Vector3::new(
+            (1.0 - a.x * a.x).max(0.0).sqrt(),
+            (1.0 - a.y * a.y).max(0.0).sqrt(),
+            (1.0 - a.z * a.z).max(0.0).sqrt(),
+        ) * self.radius;
+        let bottom = Aabb::new(self.base - extent, self.base + extent);
+        let top = Aabb::new(self.top() - extent, self.top() + extent);
+        Some(bottom.surrounding(&top))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cylinder() -> Cylinder {
+        Cylinder::new(
+            Vector3::new(0.0, -1.0, -5.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            1.0,
+            2.0,
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn ray_hits_side_head_on() {
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let hit = cylinder().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 4.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn grazing_ray_touches_side() {
+        let grazing = Ray::new(Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = cylinder().ray_intersect(&grazing).unwrap();
+        assert_eq!(hit.t, 5.0);
+        assert_eq!(hit.normal, Vector3::new(1.0, 0.0, 0.0));
+
+        let outside = Ray::new(Vector3::new(1.001, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(cylinder().ray_intersect(&outside), None);
+    }
+
+    #[test]
+    fn ray_from_above_hits_top_cap() {
+        let ray = Ray::new(Vector3::new(0.5, 5.0, -5.0), Vector3::new(0.0, -1.0, 0.0));
+        let hit = cylinder().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 4.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn ray_from_below_hits_bottom_cap() {
+        let ray = Ray::new(Vector3::new(0.5, -5.0, -5.0), Vector3::new(0.0, 1.0, 0.0));
+        let hit = cylinder().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 4.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, -1.0, 0.0));
+        assert!(hit.front_face);
+    }
+
+    #[test]
+    fn bounding_box_spans_both_caps() {
+        let bbox = cylinder().bounding_box().unwrap();
+        assert_eq!(bbox.min, Vector3::new(-1.0, -1.0, -6.0));
+        assert_eq!(bbox.max, Vector3::new(1.0, 1.0, -4.0));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Aabb, HitRecord, Hittable, Material, Ray, Vector3};
+
+/// A flat, round disk facing `normal`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Disk {
+    pub center: Vector3,
+    pub normal: Vector3,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl Disk {
+    pub fn new(center: Vector3, normal: Vector3, radius: f64, material: Material) -> Disk {
+        Disk {
+            center,
+            normal: normal.normalize(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        let denom = self.normal * ray.direction;
+        if denom.abs() < 1e-6 {
+            return None;
+        }
+        let t = (self.center - ray.origin) * self.normal / denom;
+        if t < 0.0 {
+            return None;
+        }
+        let point = ray.at(t);
+        if point.distance_squared(&self.center) > self.radius * self.radius {
+            return None;
+        }
+        let (front_face, normal) = HitRecord::face_normal(ray, self.normal);
+        Some(HitRecord {
+            t,
+            point,
+            normal,
+            front_face,
+            object_id: None,
+            material: self.material.clone(),
+            u: 0.0,
+            v: 0.0,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // along each axis the rim reaches out by the radius, shrunk by how much
+        // the disk is tilted away from that axis
+        let n = self.normal;
+        let extent = Vector3::new(
+            (1.0 - n.x * n.x).max(0.0).sqrt(),
+            (1.0 - n.y * n.y).max(0.0).sqrt(),
+            (1.0 - n.z * n.z).max(0.0).sqrt(),
+        ) * self.radius;
+        Some(Aabb::new(self.center - extent, self.center + extent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk() -> Disk {
+        Disk::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            1.0,
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn ray_hits_inside_radius() {
+        let ray = Ray::new(Vector3::new(0.0, 0.99, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = disk().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 5.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn ray_misses_just_outside_radius() {
+        let ray = Ray::new(Vector3::new(0.0, 1.01, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(disk().ray_intersect(&ray), None);
+    }
+}
@@ -0,0 +1,111 @@
+use std::f64::consts::PI;
+use std::io;
+
+use image::ImageError;
+
+use crate::Vector3;
+
+/// A panorama surrounding the scene, seen by rays that miss everything. The
+/// pixels are in linear color and use the equirectangular layout: longitude
+/// across, from -Z round through +X, and latitude down, from +Y to -Y.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vector3>,
+}
+
+impl EnvironmentMap {
+    /// A map of `pixels`, row by row from the top-left corner, or `None`
+    /// unless there are exactly `width` by `height` of them and at least one.
+    pub fn new(width: usize, height: usize, pixels: Vec<Vector3>) -> Option<EnvironmentMap> {
+        (width > 0 && height > 0 && pixels.len() == width * height).then_some(EnvironmentMap {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Reads an image, undoing its gamma so the pixels are linear.
+    pub fn load(path: &str) -> io::Result<EnvironmentMap> {
+        let image = image::open(path)
+            .map_err(|err| match err {
+                ImageError::IoError(err) => err,
+                err => io::Error::new(io::ErrorKind::InvalidData, err),
+            })?
+            .to_rgb32f();
+        let pixels = image
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b] = pixel.0.map(|c| (c as f64).powf(2.2));
+                Vector3::new(r, g, b)
+            })
+            .collect();
+        EnvironmentMap::new(image.width() as usize, image.height() as usize, pixels)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "empty image"))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The pixels, row by row from the top-left corner.
+    pub fn into_pixels(self) -> Vec<Vector3> {
+        self.pixels
+    }
+
+    /// Index into `pixels` of the texel seen looking along `dir`, which must
+    /// be unit-length.
+    pub fn pixel_index(&self, dir: &Vector3) -> usize {
+        let u = 0.5 + dir.x.atan2(-dir.z) / (2.0 * PI);
+        let v = dir.y.clamp(-1.0, 1.0).acos() / PI;
+        let x = ((u * self.width as f64) as usize).min(self.width - 1);
+        let y = ((v * self.height as f64) as usize).min(self.height - 1);
+        y * self.width + x
+    }
+
+    pub fn sample(&self, dir: &Vector3) -> Vector3 {
+        self.pixels[self.pixel_index(dir)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(width: usize, height: usize) -> EnvironmentMap {
+        let pixels = (0..width * height)
+            .map(|i| Vector3::new(i as f64, 0.0, 0.0))
+            .collect();
+        EnvironmentMap::new(width, height, pixels).unwrap()
+    }
+
+    #[test]
+    fn directions_map_to_equirectangular_pixels() {
+        let env = map(4, 2);
+        // straight ahead is the middle of the image
+        assert_eq!(env.pixel_index(&Vector3::new(0.0, 0.0, -1.0)), 6);
+        // +X is a quarter turn further along
+        assert_eq!(env.pixel_index(&Vector3::new(1.0, 0.0, 0.0)), 7);
+        // straight up is the top row, straight down the bottom one
+        assert!(env.pixel_index(&Vector3::new(0.0, 1.0, 0.0)) < 4);
+        assert!(env.pixel_index(&Vector3::new(0.0, -1.0, 0.0)) >= 4);
+        assert_eq!(env.sample(&Vector3::new(0.0, 0.0, -1.0)).x, 6.0);
+    }
+
+    #[test]
+    fn pixels_must_fill_the_map() {
+        assert!(EnvironmentMap::new(2, 1, vec![Vector3::new_zero(); 2]).is_some());
+        assert!(EnvironmentMap::new(2, 2, vec![Vector3::new_zero(); 2]).is_none());
+        assert!(EnvironmentMap::new(0, 0, vec![]).is_none());
+    }
+
+    #[test]
+    fn loading_missing_file_is_an_error() {
+        assert!(EnvironmentMap::load("/nonexistent-directory/sky.png").is_err());
+    }
+}
@@ -0,0 +1,2134 @@
+use std::error::Error;
+use std::fmt;
+use std::num::ParseFloatError;
+use std::ops;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod aabb;
+mod background;
+mod box_primitive;
+mod bvh;
+mod camera;
+mod cylinder;
+mod disk;
+mod environment;
+mod matrix;
+mod mesh;
+mod moving_sphere;
+mod obj;
+mod output;
+mod quad;
+mod render;
+mod rng;
+mod scene;
+mod texture;
+mod timing;
+mod toggle;
+mod transformed;
+mod triangle;
+
+pub use aabb::Aabb;
+pub use background::Background;
+pub use box_primitive::BoxPrimitive;
+pub use bvh::BvhNode;
+pub use camera::{AdaptiveSampling, Camera, Projection};
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use environment::EnvironmentMap;
+pub use matrix::Matrix4;
+pub use mesh::Mesh;
+pub use moving_sphere::MovingSphere;
+pub use obj::{load_obj, parse_obj};
+pub use output::{
+    color_to_rgb8, post_process, tone_map_reinhard, write_png, write_ppm, write_ppm_binary,
+    BitDepth, PostProcess, ToneMap,
+};
+pub use quad::Quad;
+pub use render::{
+    render_framebuffer, render_framebuffer_with_progress, render_sequence, render_tiled,
+    render_to_buffer, with_threads, Tile,
+};
+pub use rng::Rng;
+#[cfg(feature = "serde")]
+pub use scene::{load_scene, SceneError};
+pub use scene::{RenderMode, Scene, SceneDescription};
+pub use texture::{Filter, Texture};
+pub use timing::Timer;
+pub use toggle::Toggle;
+pub use transformed::Transformed;
+pub use triangle::Triangle;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Vector3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vector3 {
+    pub const fn new(x: f64, y: f64, z: f64) -> Vector3 {
+        Vector3 { x, y, z }
+    }
+
+    pub const fn new_zero() -> Vector3 {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn norm(&self) -> f64 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn from_array(a: [f64; 3]) -> Vector3 {
+        Vector3::new(a[0], a[1], a[2])
+    }
+
+    pub fn to_array(&self) -> [f64; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// Squared norm, for comparisons that don't need the square root.
+    pub fn length_squared(&self) -> f64 {
+        *self * *self
+    }
+
+    /// The unit vector with the same direction. The zero vector has no
+    /// direction and normalizes to all-NaN components; use
+    /// [`Vector3::try_normalize`] when the input may be degenerate.
+    pub fn normalize(&self) -> Vector3 {
+        *self / self.norm()
+    }
+
+    /// Like [`Vector3::normalize`], scaling `self` in place.
+    pub fn normalize_mut(&mut self) {
+        *self *= 1.0 / self.norm();
+    }
+
+    /// The unit vector with the same direction, or `None` if the vector is
+    /// too short for its direction to be meaningful.
+    pub fn try_normalize(&self) -> Option<Vector3> {
+        let norm = self.norm();
+        if norm < 1e-12 {
+            None
+        } else {
+            Some(*self / norm)
+        }
+    }
+
+    /// Component-wise (Hadamard) product, used to modulate colors. Not to be
+    /// confused with `*`, which is the dot product.
+    pub fn componentwise(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+
+    /// Linear interpolation: `self` at `t` = 0, `other` at `t` = 1.
+    pub fn lerp(&self, other: &Vector3, t: f64) -> Vector3 {
+        *self * (1.0 - t) + *other * t
+    }
+
+    /// Bilinear interpolation over a 2x2 grid: `cuv` at `u` = 0 or 1 and
+    /// `v` = 0 or 1, blending along `u` first.
+    pub fn bilerp(
+        c00: &Vector3,
+        c10: &Vector3,
+        c01: &Vector3,
+        c11: &Vector3,
+        u: f64,
+        v: f64,
+    ) -> Vector3 {
+        c00.lerp(c10, u).lerp(&c01.lerp(c11, u), v)
+    }
+
+    pub fn distance(&self, other: &Vector3) -> f64 {
+        (*self - *other).norm()
+    }
+
+    pub fn distance_squared(&self, other: &Vector3) -> f64 {
+        (*self - *other).length_squared()
+    }
+
+    /// Whether every component is within `epsilon` of the matching one in
+    /// `other`.
+    pub fn approx_eq(&self, other: &Vector3, epsilon: f64) -> bool {
+        (self.x - other.x).abs() <= epsilon
+            && (self.y - other.y).abs() <= epsilon
+            && (self.z - other.z).abs() <= epsilon
+    }
+
+    /// Clamps every component to [0, 1].
+    pub fn clamp01(&self) -> Vector3 {
+        self.clamp(0.0, 1.0)
+    }
+
+    /// Same as [`Vector3::clamp01`], under the name color code tends to use.
+    pub fn saturate(&self) -> Vector3 {
+        self.clamp01()
+    }
+
+    /// Rounds every component down.
+    pub fn floor(&self) -> Vector3 {
+        Vector3::new(self.x.floor(), self.y.floor(), self.z.floor())
+    }
+
+    /// Rounds every component up.
+    pub fn ceil(&self) -> Vector3 {
+        Vector3::new(self.x.ceil(), self.y.ceil(), self.z.ceil())
+    }
+
+    /// How far each component is above its floor, in [0, 1). Unlike
+    /// [`f64::fract`] this wraps negative values too, so -0.3 gives 0.7.
+    pub fn fract(&self) -> Vector3 {
+        *self - self.floor()
+    }
+
+    /// Raises every component to the power `exp`, as in gamma correction.
+    pub fn powf(&self, exp: f64) -> Vector3 {
+        Vector3::new(self.x.powf(exp), self.y.powf(exp), self.z.powf(exp))
+    }
+
+    /// Clamps every component to [`min`, `max`].
+    pub fn clamp(&self, min: f64, max: f64) -> Vector3 {
+        Vector3::new(
+            self.x.clamp(min, max),
+            self.y.clamp(min, max),
+            self.z.clamp(min, max),
+        )
+    }
+
+    /// Clamps each component to the matching components of `min` and `max`.
+    pub fn clamp_vec(&self, min: &Vector3, max: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.x.clamp(min.x, max.x),
+            self.y.clamp(min.y, max.y),
+            self.z.clamp(min.z, max.z),
+        )
+    }
+
+    /// The smaller of each pair of components.
+    pub fn min(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    /// The larger of each pair of components.
+    pub fn max(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    /// Whether no component is NaN or infinite.
+    pub fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
+    /// Perceived brightness of `self` as a linear RGB color (Rec. 709).
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.x + 0.7152 * self.y + 0.0722 * self.z
+    }
+
+    pub fn min_component(&self) -> f64 {
+        self.x.min(self.y).min(self.z)
+    }
+
+    pub fn max_component(&self) -> f64 {
+        self.x.max(self.y).max(self.z)
+    }
+
+    /// Mirrors `self` about the surface with the given `normal`, which must
+    /// be unit-length.
+    pub fn reflect(&self, normal: &Vector3) -> Vector3 {
+        *self - *normal * 2.0 * (*self * *normal)
+    }
+
+    /// Mirrors the point `self` across the plane through `plane_point`
+    /// facing `plane_normal`, which need not be unit-length. Unlike
+    /// [`Vector3::reflect`], this treats `self` as a position.
+    pub fn reflect_about_plane(&self, plane_point: &Vector3, plane_normal: &Vector3) -> Vector3 {
+        let offset = *self - *plane_point;
+        *plane_point + offset.reflect(&plane_normal.normalize())
+    }
+
+    /// Bends the unit vector `self` through the surface with the given unit
+    /// `normal` (Snell's law), where `normal` faces against `self` and
+    /// `eta_ratio` is the index of the incident medium over the index of the
+    /// transmitting one. Returns `None` on total internal reflection.
+    pub fn refract(&self, normal: &Vector3, eta_ratio: f64) -> Option<Vector3> {
+        let cosi = -(*self * *normal).clamp(-1.0, 1.0);
+        let k = 1.0 - eta_ratio * eta_ratio * (1.0 - cosi * cosi);
+        if k < 0.0 {
+            None
+        } else {
+            Some(*self * eta_ratio + *normal * (eta_ratio * cosi - k.sqrt()))
+        }
+    }
+
+    /// A direction drawn uniformly from the unit sphere.
+    pub fn random_unit(rng: &mut Rng) -> Vector3 {
+        // a uniform height along the axis gives a uniform sphere (Archimedes)
+        let z = 2.0 * rng.next_f64() - 1.0;
+        let phi = 2.0 * std::f64::consts::PI * rng.next_f64();
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        Vector3::new(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    /// A direction drawn uniformly from the hemisphere around `normal`.
+    pub fn random_in_hemisphere(normal: &Vector3, rng: &mut Rng) -> Vector3 {
+        let v = Vector3::random_unit(rng);
+        if v * *normal < 0.0 {
+            -v
+        } else {
+            v
+        }
+    }
+
+    /// Angle to `other` in radians, in [0, π].
+    pub fn angle_between(&self, other: &Vector3) -> f64 {
+        // rounding can push the cosine just past ±1, where acos is NaN
+        let cos = (*self * *other) / (self.norm() * other.norm());
+        cos.clamp(-1.0, 1.0).acos()
+    }
+
+    /// The component of `self` along `onto`, which must not be zero.
+    pub fn project_onto(&self, onto: &Vector3) -> Vector3 {
+        *onto * ((*self * *onto) / (*onto * *onto))
+    }
+
+    /// The component of `self` perpendicular to `onto`.
+    pub fn reject_from(&self, onto: &Vector3) -> Vector3 {
+        *self - self.project_onto(onto)
+    }
+
+    pub fn cross(&self, other: &Vector3) -> Vector3 {
+        Vector3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+impl From<[f64; 3]> for Vector3 {
+    fn from(a: [f64; 3]) -> Vector3 {
+        Vector3::from_array(a)
+    }
+}
+
+impl From<Vector3> for [f64; 3] {
+    fn from(v: Vector3) -> [f64; 3] {
+        v.to_array()
+    }
+}
+
+/// Why a string couldn't be parsed as a [`Vector3`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseVectorError {
+    /// The string held this many comma-separated components instead of 3.
+    WrongCount(usize),
+    /// A component, given here, wasn't a number.
+    InvalidComponent(String, ParseFloatError),
+}
+
+impl fmt::Display for ParseVectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseVectorError::WrongCount(count) => {
+                write!(f, "expected 3 comma-separated components, got {}", count)
+            }
+            ParseVectorError::InvalidComponent(component, err) => {
+                write!(f, "invalid component {:?}: {}", component, err)
+            }
+        }
+    }
+}
+
+impl Error for ParseVectorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseVectorError::WrongCount(_) => None,
+            ParseVectorError::InvalidComponent(_, err) => Some(err),
+        }
+    }
+}
+
+/// Parses comma-separated components such as `"1.0,2.0,3.0"`, allowing
+/// whitespace around each.
+impl TryFrom<&str> for Vector3 {
+    type Error = ParseVectorError;
+
+    fn try_from(s: &str) -> Result<Vector3, ParseVectorError> {
+        let components: Vec<&str> = s.split(',').map(str::trim).collect();
+        let [x, y, z] = components[..] else {
+            return Err(ParseVectorError::WrongCount(components.len()));
+        };
+        let parse = |component: &str| {
+            component
+                .parse()
+                .map_err(|err| ParseVectorError::InvalidComponent(component.to_string(), err))
+        };
+        Ok(Vector3::new(parse(x)?, parse(y)?, parse(z)?))
+    }
+}
+
+/// Components by axis: 0 is x, 1 is y and 2 is z.
+impl ops::Index<usize> for Vector3 {
+    type Output = f64;
+
+    fn index(&self, axis: usize) -> &f64 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vector3 index out of range: {}", axis),
+        }
+    }
+}
+
+impl ops::Add<Vector3> for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl ops::Sub<Vector3> for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+/// Dot product.
+impl ops::Mul<Vector3> for Vector3 {
+    type Output = f64;
+
+    fn mul(self, other: Vector3) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+}
+
+impl ops::Mul<f64> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, k: f64) -> Vector3 {
+        Vector3::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+/// Component-wise division. Dividing by zero follows IEEE 754, giving
+/// infinite or NaN components rather than panicking.
+impl ops::Div<f64> for Vector3 {
+    type Output = Vector3;
+
+    fn div(self, k: f64) -> Vector3 {
+        Vector3::new(self.x / k, self.y / k, self.z / k)
+    }
+}
+
+impl ops::AddAssign<Vector3> for Vector3 {
+    fn add_assign(&mut self, other: Vector3) {
+        *self = *self + other;
+    }
+}
+
+impl ops::SubAssign<Vector3> for Vector3 {
+    fn sub_assign(&mut self, other: Vector3) {
+        *self = *self - other;
+    }
+}
+
+impl ops::MulAssign<f64> for Vector3 {
+    fn mul_assign(&mut self, k: f64) {
+        *self = *self * k;
+    }
+}
+
+impl ops::Neg for Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Vector3 {
+        Vector3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl ops::Add<&Vector3> for &Vector3 {
+    type Output = Vector3;
+
+    fn add(self, other: &Vector3) -> Vector3 {
+        *self + *other
+    }
+}
+
+impl ops::Sub<&Vector3> for &Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, other: &Vector3) -> Vector3 {
+        *self - *other
+    }
+}
+
+impl ops::Mul<f64> for &Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, k: f64) -> Vector3 {
+        *self * k
+    }
+}
+
+impl ops::Neg for &Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Vector3 {
+        -*self
+    }
+}
+
+/// A half-line starting at `origin` and going along `direction`, which is
+/// always unit-length.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3,
+    pub direction: Vector3,
+    /// When the ray was cast, in [0, 1) over the time the shutter is open;
+    /// moving objects are hit where they were at that moment.
+    pub time: f64,
+}
+
+impl Ray {
+    /// A ray cast at time 0.
+    pub fn new(origin: Vector3, direction: Vector3) -> Ray {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+            time: 0.0,
+        }
+    }
+
+    /// The same ray, cast at `time` instead.
+    pub fn with_time(self, time: f64) -> Ray {
+        Ray { time, ..self }
+    }
+
+    /// Point reached after travelling a distance `t` along the ray.
+    pub fn at(&self, t: f64) -> Vector3 {
+        self.origin + self.direction * t
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default = "Material::white"))]
+pub struct Material {
+    #[cfg_attr(feature = "serde", serde(rename = "color"))]
+    pub texture: Texture,
+    /// Weights of the diffuse, specular, reflected and refracted terms, in
+    /// that order.
+    pub albedo: [f64; 4],
+    pub specular_exponent: f64,
+    pub refractive_index: f64,
+    /// Light given off by the surface itself, added regardless of lighting.
+    pub emission: Vector3,
+    /// Texture coordinates are multiplied by `uv_scale` and then shifted by
+    /// `uv_offset` before sampling, to tile a texture or move it around.
+    pub uv_scale: f64,
+    pub uv_offset: (f64, f64),
+    /// Metals tint what they reflect with their own color.
+    pub metallic: bool,
+    /// How far reflections scatter from the mirror direction, in [0, 1]; 0
+    /// is a perfect mirror.
+    pub fuzz: f64,
+    /// Clear materials like glass, which the path tracer follows along
+    /// either the reflected or the refracted ray, chosen at random, rather
+    /// than both.
+    pub dielectric: bool,
+}
+
+impl Material {
+    /// A purely diffuse material of the given color.
+    pub fn new(color: Vector3) -> Material {
+        Material {
+            texture: Texture::SolidColor(color),
+            albedo: [1.0, 0.0, 0.0, 0.0],
+            specular_exponent: 0.0,
+            refractive_index: 1.0,
+            emission: Vector3::new_zero(),
+            uv_scale: 1.0,
+            uv_offset: (0.0, 0.0),
+            metallic: false,
+            fuzz: 0.0,
+            dielectric: false,
+        }
+    }
+
+    /// A clear material such as glass or water.
+    pub fn dielectric(refractive_index: f64) -> Material {
+        Material {
+            albedo: [0.0, 0.0, 0.0, 1.0],
+            refractive_index,
+            dielectric: true,
+            ..Material::new(Vector3::new(1.0, 1.0, 1.0))
+        }
+    }
+
+    /// A metal reflecting light tinted by `albedo`, blurred by `fuzz`, which
+    /// is clamped to [0, 1].
+    pub fn metal(albedo: Vector3, fuzz: f64) -> Material {
+        Material {
+            albedo: [0.0, 0.0, 1.0, 0.0],
+            metallic: true,
+            fuzz: fuzz.clamp(0.0, 1.0),
+            ..Material::new(albedo)
+        }
+    }
+
+    /// Direction in which `incident` leaves the surface with unit `normal`
+    /// when reflected, perturbed at random by the material's fuzz.
+    pub fn reflect_direction(
+        &self,
+        incident: &Vector3,
+        normal: &Vector3,
+        rng: &mut Rng,
+    ) -> Vector3 {
+        let mirrored = reflect(incident, normal).normalize();
+        if self.fuzz <= 0.0 {
+            return mirrored;
+        }
+        (mirrored + rng.random_in_unit_sphere() * self.fuzz)
+            .try_normalize()
+            .unwrap_or(mirrored)
+    }
+
+    /// Base color at texture coordinates (`u`, `v`).
+    pub fn color_at(&self, u: f64, v: f64) -> Vector3 {
+        let (du, dv) = self.uv_offset;
+        self.texture
+            .sample(u * self.uv_scale + du, v * self.uv_scale + dv)
+    }
+
+    /// Scatters `ray` off a diffuse surface at `hit` in a random, roughly
+    /// cosine-weighted direction, returning the bounced ray and how much of
+    /// its light the surface passes on. Dielectrics instead reflect or
+    /// refract it, reflecting with the Fresnel reflectance as probability.
+    /// `None` if the material has no diffuse part.
+    pub fn scatter(&self, ray: &Ray, hit: &HitRecord, rng: &mut Rng) -> Option<(Ray, Vector3)> {
+        if self.dielectric {
+            let eta = hit.relative_index();
+            let reflectance = fresnel(&ray.direction, &hit.normal, eta);
+            let direction = if rng.next_f64() < reflectance {
+                reflect(&ray.direction, &hit.normal)
+            } else {
+                refract(&ray.direction, &hit.normal, eta, 1.0)
+            };
+            // start on whichever side of the surface the ray leaves from
+            let offset = if direction * hit.normal < 0.0 {
+                -hit.normal * 1e-3
+            } else {
+                hit.normal * 1e-3
+            };
+            return Some((
+                Ray::new(hit.point + offset, direction).with_time(ray.time),
+                self.color_at(hit.u, hit.v),
+            ));
+        }
+        if self.albedo[0] <= 0.0 {
+            return None;
+        }
+        let direction = (hit.normal + rng.random_in_unit_sphere())
+            .try_normalize()
+            .unwrap_or(hit.normal);
+        Some((
+            Ray::new(hit.point + hit.normal * 1e-3, direction).with_time(ray.time),
+            self.color_at(hit.u, hit.v) * self.albedo[0],
+        ))
+    }
+
+    /// Fills in the fields a scene file leaves out.
+    #[cfg(feature = "serde")]
+    fn white() -> Material {
+        Material::new(Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn ivory() -> Material {
+        Material {
+            albedo: [0.6, 0.3, 0.1, 0.0],
+            specular_exponent: 50.0,
+            ..Material::new(Vector3::new(0.4, 0.4, 0.3))
+        }
+    }
+
+    pub fn glass() -> Material {
+        Material {
+            albedo: [0.0, 0.5, 0.1, 0.8],
+            specular_exponent: 125.0,
+            refractive_index: 1.5,
+            ..Material::new(Vector3::new(0.6, 0.7, 0.8))
+        }
+    }
+
+    pub fn red_rubber() -> Material {
+        Material {
+            albedo: [0.9, 0.1, 0.0, 0.0],
+            specular_exponent: 10.0,
+            ..Material::new(Vector3::new(0.3, 0.1, 0.1))
+        }
+    }
+
+    pub fn mirror() -> Material {
+        Material {
+            albedo: [0.0, 10.0, 0.8, 0.0],
+            specular_exponent: 1425.0,
+            ..Material::new(Vector3::new(1.0, 1.0, 1.0))
+        }
+    }
+}
+
+impl Default for Material {
+    /// Matte gray.
+    fn default() -> Material {
+        Material::new(Vector3::new(0.5, 0.5, 0.5))
+    }
+}
+
+/// Where a ray met a surface and what the surface looks like there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitRecord {
+    pub t: f64,
+    pub point: Vector3,
+    /// Faces against the incoming ray, so on the inside of a closed surface
+    /// it points inwards.
+    pub normal: Vector3,
+    /// Whether the ray hit the outside of the surface, the side its outward
+    /// normal points to.
+    pub front_face: bool,
+    /// Index of the object hit among those searched, such as a scene's
+    /// `objects`; `None` when intersecting a single object directly.
+    pub object_id: Option<usize>,
+    pub material: Material,
+    /// Texture coordinates of the point; (0, 0) on surfaces that don't have
+    /// any.
+    pub u: f64,
+    pub v: f64,
+}
+
+impl HitRecord {
+    /// Whether `ray` hits the outside of a surface with `outward_normal`
+    /// there, and the normal turned to face against the ray.
+    pub fn face_normal(ray: &Ray, outward_normal: Vector3) -> (bool, Vector3) {
+        if ray.direction * outward_normal > 0.0 {
+            (false, -outward_normal)
+        } else {
+            (true, outward_normal)
+        }
+    }
+
+    /// Refractive index of the material on the far side of the surface over
+    /// that of the near side, taking the outside to be air.
+    pub fn relative_index(&self) -> f64 {
+        if self.front_face {
+            self.material.refractive_index
+        } else {
+            1.0 / self.material.refractive_index
+        }
+    }
+}
+
+/// Anything a ray can be intersected with.
+pub trait Hittable: Send + Sync {
+    /// The nearest intersection in front of the ray origin, if any.
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord>;
+
+    /// A box enclosing the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+}
+
+impl<T: Hittable + ?Sized> Hittable for Box<T> {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        (**self).ray_intersect(ray)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sphere {
+    center: Vector3,
+    radius: f64,
+    material: Material,
+}
+
+impl Sphere {
+    pub fn new(center: Vector3, radius: f64, material: Material) -> Sphere {
+        Sphere {
+            center,
+            radius,
+            material,
+        }
+    }
+
+    pub fn center(&self) -> Vector3 {
+        self.center
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Borrowed rather than copied, as materials can hold whole image
+    /// textures; clone it for a `Material` of your own.
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+}
+
+/// Nearest hit of `ray` on the sphere around `center`, shared by spheres that
+/// stay put and ones that move.
+pub(crate) fn intersect_sphere(
+    center: Vector3,
+    radius: f64,
+    material: &Material,
+    ray: &Ray,
+) -> Option<HitRecord> {
+    let l = center - ray.origin;
+    let tca = l * ray.direction;
+    let d2 = l.length_squared() - tca * tca;
+    if d2 > radius * radius {
+        return None;
+    }
+    let thc = (radius * radius - d2).sqrt();
+    let t0 = tca - thc;
+    let t1 = tca + thc;
+    let t = if t0 >= 0.0 {
+        t0
+    } else if t1 >= 0.0 {
+        t1
+    } else {
+        return None;
+    };
+    let point = ray.at(t);
+    let outward = (point - center).normalize();
+    let (front_face, normal) = HitRecord::face_normal(ray, outward);
+    // longitude across from -Z round through +X, latitude down from +Y
+    Some(HitRecord {
+        t,
+        point,
+        normal,
+        front_face,
+        object_id: None,
+        material: material.clone(),
+        u: 0.5 + outward.x.atan2(-outward.z) / (2.0 * std::f64::consts::PI),
+        v: outward.y.clamp(-1.0, 1.0).acos() / std::f64::consts::PI,
+    })
+}
+
+impl Hittable for Sphere {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        intersect_sphere(self.center, self.radius, &self.material, ray)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vector3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+}
+
+/// An infinite plane through `point`, facing `normal`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Plane {
+    pub point: Vector3,
+    pub normal: Vector3,
+    pub material: Material,
+    /// When set, the plane is a checkerboard of squares alternating between
+    /// the material's texture and this color. The squares are a unit wide,
+    /// scaled and shifted along x and z by the material's `uv_scale` and
+    /// `uv_offset`.
+    pub checker_color: Option<Vector3>,
+}
+
+impl Plane {
+    pub fn new(point: Vector3, normal: Vector3, material: Material) -> Plane {
+        Plane {
+            point,
+            normal: normal.normalize(),
+            material,
+            checker_color: None,
+        }
+    }
+
+    pub fn checkerboard(
+        point: Vector3,
+        normal: Vector3,
+        material: Material,
+        checker_color: Vector3,
+    ) -> Plane {
+        Plane {
+            checker_color: Some(checker_color),
+            ..Plane::new(point, normal, material)
+        }
+    }
+
+    /// Material at a point lying on the plane.
+    pub fn material_at(&self, point: &Vector3) -> Material {
+        let (du, dv) = self.material.uv_offset;
+        let u = point.x * self.material.uv_scale + du;
+        let v = point.z * self.material.uv_scale + dv;
+        match self.checker_color {
+            Some(color) if (u.floor() + v.floor()) as i64 % 2 != 0 => Material {
+                texture: Texture::SolidColor(color),
+                ..self.material.clone()
+            },
+            _ => self.material.clone(),
+        }
+    }
+}
+
+impl Hittable for Plane {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        let denom = self.normal * ray.direction;
+        if denom.abs() < 1e-6 {
+            return None;
+        }
+        let t = (self.point - ray.origin) * self.normal / denom;
+        if t < 0.0 {
+            return None;
+        }
+        let point = ray.at(t);
+        let (front_face, normal) = HitRecord::face_normal(ray, self.normal);
+        // the texture repeats over every unit square of x and z
+        let wrapped = point.fract();
+        Some(HitRecord {
+            t,
+            point,
+            normal,
+            front_face,
+            object_id: None,
+            material: self.material_at(&point),
+            u: wrapped.x,
+            v: wrapped.z,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+/// Mirrors `incident` about the surface with the given `normal`, which must
+/// be unit-length.
+pub fn reflect(incident: &Vector3, normal: &Vector3) -> Vector3 {
+    incident.reflect(normal)
+}
+
+/// Mirrors the position `point` across the plane through `plane_point`
+/// facing `plane_normal`.
+pub fn reflect_point(point: &Vector3, plane_point: &Vector3, plane_normal: &Vector3) -> Vector3 {
+    point.reflect_about_plane(plane_point, plane_normal)
+}
+
+/// Bends `incident` through the surface with the given unit `normal`, going
+/// from a medium of index `eta_i` into one of index `eta_t` (Snell's law).
+/// Under total internal reflection the incident vector is mirrored instead.
+pub fn refract(incident: &Vector3, normal: &Vector3, eta_t: f64, eta_i: f64) -> Vector3 {
+    let cosi = -(*incident * *normal).clamp(-1.0, 1.0);
+    if cosi < 0.0 {
+        // the ray comes from inside the object: flip the normal and swap the media
+        return refract(incident, &-*normal, eta_i, eta_t);
+    }
+    incident
+        .refract(normal, eta_i / eta_t)
+        .unwrap_or_else(|| incident.reflect(normal))
+}
+
+/// Fraction of light reflected rather than refracted by a surface of index
+/// `refractive_index` in air, by Schlick's approximation. Rays hitting the
+/// back of the surface are treated as leaving the medium.
+pub fn fresnel(incident: &Vector3, normal: &Vector3, refractive_index: f64) -> f64 {
+    let cosi = -(*incident * *normal).clamp(-1.0, 1.0);
+    let (eta_i, eta_t, cosi) = if cosi < 0.0 {
+        (refractive_index, 1.0, -cosi)
+    } else {
+        (1.0, refractive_index, cosi)
+    };
+    let r0 = ((eta_i - eta_t) / (eta_i + eta_t)).powi(2);
+    // going into a less dense medium, the angle that matters is the refracted one
+    let cos = if eta_i > eta_t {
+        let sint2 = (eta_i / eta_t).powi(2) * (1.0 - cosi * cosi);
+        if sint2 > 1.0 {
+            return 1.0;
+        }
+        (1.0 - sint2).sqrt()
+    } else {
+        cosi
+    };
+    r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+}
+
+/// A light source. Point lights are written in JSON with a `position` and
+/// directional lights with a `direction`; spotlights have both, and their
+/// cone angles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum Light {
+    // listed first, as untagged JSON would otherwise read it as a point light
+    /// A point light shining only along a cone around `direction`, at full
+    /// intensity within `inner_angle` of it and fading out smoothly to
+    /// nothing at `outer_angle`, both in radians.
+    Spot {
+        position: Vector3,
+        direction: Vector3,
+        intensity: f64,
+        inner_angle: f64,
+        outer_angle: f64,
+    },
+    Point {
+        position: Vector3,
+        intensity: f64,
+        /// Whether the intensity falls off with the inverse square of the
+        /// distance; otherwise it is the same everywhere.
+        #[cfg_attr(feature = "serde", serde(default))]
+        attenuated: bool,
+        /// Size of the light, which softens the edges of its shadows. Zero
+        /// gives hard shadows.
+        #[cfg_attr(feature = "serde", serde(default))]
+        radius: f64,
+    },
+    /// A light infinitely far away, like the sun, shining along `direction`.
+    Directional { direction: Vector3, intensity: f64 },
+}
+
+impl Light {
+    pub fn new(position: Vector3, intensity: f64) -> Light {
+        Light::Point {
+            position,
+            intensity,
+            attenuated: false,
+            radius: 0.0,
+        }
+    }
+
+    /// A point light whose intensity falls off with the square of the
+    /// distance.
+    pub fn attenuated(position: Vector3, intensity: f64) -> Light {
+        Light::Point {
+            position,
+            intensity,
+            attenuated: true,
+            radius: 0.0,
+        }
+    }
+
+    /// A point light spread over a sphere of the given `radius`, casting
+    /// soft shadows.
+    pub fn spherical(position: Vector3, intensity: f64, radius: f64) -> Light {
+        Light::Point {
+            position,
+            intensity,
+            attenuated: false,
+            radius,
+        }
+    }
+
+    pub fn directional(direction: Vector3, intensity: f64) -> Light {
+        Light::Directional {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+
+    pub fn spot(
+        position: Vector3,
+        direction: Vector3,
+        intensity: f64,
+        inner_angle: f64,
+        outer_angle: f64,
+    ) -> Light {
+        Light::Spot {
+            position,
+            direction: direction.normalize(),
+            intensity,
+            inner_angle,
+            outer_angle,
+        }
+    }
+
+    /// Unit vector from `point` towards the light, and the distance to it.
+    /// Directional lights are infinitely far away.
+    pub fn direction_from(&self, point: &Vector3) -> (Vector3, f64) {
+        match *self {
+            Light::Point { position, .. } | Light::Spot { position, .. } => {
+                ((position - *point).normalize(), point.distance(&position))
+            }
+            Light::Directional { direction, .. } => (-direction.normalize(), f64::INFINITY),
+        }
+    }
+
+    /// Like [`Light::direction_from`], but towards a random point inside a
+    /// spherical light.
+    pub fn sample_direction_from(&self, point: &Vector3, rng: &mut Rng) -> (Vector3, f64) {
+        match *self {
+            Light::Point {
+                position, radius, ..
+            } if radius > 0.0 => {
+                let target = position + rng.random_in_unit_sphere() * radius;
+                ((target - *point).normalize(), point.distance(&target))
+            }
+            _ => self.direction_from(point),
+        }
+    }
+
+    /// Radius of a spherical light, zero for lights without size.
+    pub fn radius(&self) -> f64 {
+        match *self {
+            Light::Point { radius, .. } => radius,
+            Light::Directional { .. } | Light::Spot { .. } => 0.0,
+        }
+    }
+
+    /// Intensity received at `point`.
+    pub fn intensity_at(&self, point: &Vector3) -> f64 {
+        match *self {
+            Light::Point {
+                position,
+                intensity,
+                attenuated: true,
+                ..
+            } => intensity / position.distance_squared(point),
+            Light::Point { intensity, .. } | Light::Directional { intensity, .. } => intensity,
+            Light::Spot {
+                position,
+                direction,
+                intensity,
+                inner_angle,
+                outer_angle,
+            } => {
+                let cos = (*point - position).normalize() * direction.normalize();
+                let (cos_inner, cos_outer) = (inner_angle.cos(), outer_angle.cos());
+                if cos >= cos_inner {
+                    intensity
+                } else if cos <= cos_outer {
+                    0.0
+                } else {
+                    // smoothstep across the edge of the cone
+                    let f = (cos - cos_outer) / (cos_inner - cos_outer);
+                    intensity * f * f * (3.0 - 2.0 * f)
+                }
+            }
+        }
+    }
+}
+
+/// The nearest hit among `objects` closer than `max_distance`, with
+/// `object_id` giving the position in `objects` of the one hit. Any slice of
+/// objects will do, boxed or not.
+pub fn scene_intersect<T: Hittable>(
+    ray: &Ray,
+    objects: &[T],
+    max_distance: f64,
+) -> Option<HitRecord> {
+    nearest_hit(
+        ray,
+        objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| (index, object as &dyn Hittable)),
+        max_distance,
+    )
+}
+
+/// The nearest hit among `objects`, each given with the id to record for it.
+pub(crate) fn nearest_hit<'a>(
+    ray: &Ray,
+    objects: impl IntoIterator<Item = (usize, &'a dyn Hittable)>,
+    max_distance: f64,
+) -> Option<HitRecord> {
+    let mut nearest: Option<HitRecord> = None;
+    let mut nearest_t = max_distance;
+    for (index, object) in objects {
+        if let Some(hit) = object.ray_intersect(ray) {
+            if hit.t < nearest_t {
+                nearest_t = hit.t;
+                nearest = Some(HitRecord {
+                    object_id: Some(index),
+                    ..hit
+                });
+            }
+        }
+    }
+    nearest
+}
+
+/// Rays that have bounced more than this many times return the background.
+/// With path tracing on, diffuse bounces go deeper and only reflection and
+/// refraction stop here.
+pub const MAX_DEPTH: usize = 4;
+
+/// Background color straight up.
+pub const BACKGROUND_TOP: Vector3 = Vector3::new(0.2, 0.7, 0.8);
+/// Background color straight down.
+pub const BACKGROUND_BOTTOM: Vector3 = Vector3::new(1.0, 1.0, 1.0);
+
+/// Sky-like vertical gradient seen by a ray going along `dir`, which must be
+/// unit-length.
+pub fn background(dir: &Vector3, top: Vector3, bottom: Vector3) -> Vector3 {
+    let t = 0.5 * (dir.y + 1.0);
+    bottom.lerp(&top, t)
+}
+
+/// Shim over [`Scene::trace`] for callers holding loose objects and lights.
+pub fn cast_ray(
+    ray: &Ray,
+    objects: &[Box<dyn Hittable>],
+    lights: &[Light],
+    depth: usize,
+) -> Vector3 {
+    let lights: Vec<Toggle<Light>> = lights.iter().copied().map(Toggle::new).collect();
+    scene::Tracer::new(objects, &lights).trace(ray, depth, &mut Rng::new(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_addition() {
+        let v1 = Vector3::new(1.0, 2.0, 3.0);
+        let v2 = Vector3::new(4.0, 5.0, 6.0);
+        assert_eq!(v1 + v2, Vector3::new(5.0, 7.0, 9.0));
+    }
+
+    #[test]
+    fn vector_subtraction() {
+        let v1 = Vector3::new(1.0, 2.0, 3.0);
+        let v2 = Vector3::new(4.0, 5.0, 6.0);
+        assert_eq!(v1 - v2, Vector3::new(-3.0, -3.0, -3.0));
+    }
+
+    #[test]
+    fn vector_dot_product() {
+        let v1 = Vector3::new(1.0, 2.0, 3.0);
+        let v2 = Vector3::new(4.0, 5.0, 6.0);
+        assert_eq!(v1 * v2, 32.0);
+    }
+
+    #[test]
+    fn vector_scalar_product() {
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        assert_eq!(v * 2.0, Vector3::new(2.0, 4.0, 6.0));
+    }
+
+    #[test]
+    fn vector_scalar_division() {
+        let v = Vector3::new(2.0, 4.0, 6.0);
+        assert_eq!(v / 2.0, Vector3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn vector_division_by_zero_is_infinite() {
+        let v = Vector3::new(1.0, -1.0, 0.0) / 0.0;
+        assert_eq!(v.x, f64::INFINITY);
+        assert_eq!(v.y, f64::NEG_INFINITY);
+        assert!(v.z.is_nan());
+    }
+
+    #[test]
+    fn vector_compound_assignment() {
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        let w = Vector3::new(4.0, 5.0, 6.0);
+
+        let mut sum = v;
+        sum += w;
+        assert_eq!(sum, v + w);
+
+        let mut difference = v;
+        difference -= w;
+        assert_eq!(difference, v - w);
+
+        let mut scaled = v;
+        scaled *= 2.0;
+        assert_eq!(scaled, v * 2.0);
+    }
+
+    #[test]
+    fn vector_negation() {
+        let v = Vector3::new(1.0, -2.0, 3.0);
+        assert_eq!(-v, Vector3::new(-1.0, 2.0, -3.0));
+    }
+
+    #[test]
+    fn vector_cross_product() {
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        let y = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(x.cross(&y), Vector3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn vector_norm() {
+        let v = Vector3::new(2.0, 3.0, 6.0);
+        assert_eq!(v.norm(), 7.0);
+    }
+
+    #[test]
+    fn vector_array_round_trip() {
+        let a = [1.0, 2.0, 3.0];
+        let v = Vector3::from_array(a);
+        assert_eq!(v, Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(v.to_array(), a);
+        assert_eq!(Vector3::from(a), v);
+        let b: [f64; 3] = v.into();
+        assert_eq!(b, a);
+    }
+
+    #[test]
+    fn vector_indexing() {
+        let v = Vector3::from_array([1.0, 2.0, 3.0]);
+        assert_eq!(v[0], 1.0);
+        assert_eq!(v[1], 2.0);
+        assert_eq!(v[2], 3.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn vector_index_out_of_range() {
+        let _ = Vector3::new_zero()[3];
+    }
+
+    #[test]
+    fn vector_length_squared() {
+        let v = Vector3::new(1.0, 2.0, 2.0);
+        assert_eq!(v.length_squared(), 9.0);
+        assert!((v.length_squared() - v.norm().powi(2)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn vector_componentwise_product() {
+        let gray = Vector3::new(0.5, 0.5, 0.5);
+        let tint = Vector3::new(1.0, 0.4, 0.2);
+        assert_eq!(gray.componentwise(&tint), Vector3::new(0.5, 0.2, 0.1));
+    }
+
+    #[test]
+    fn vector_bilerp() {
+        let c00 = Vector3::new(0.0, 0.0, 0.0);
+        let c10 = Vector3::new(4.0, 0.0, 0.0);
+        let c01 = Vector3::new(0.0, 4.0, 0.0);
+        let c11 = Vector3::new(0.0, 0.0, 4.0);
+        let bilerp = |u, v| Vector3::bilerp(&c00, &c10, &c01, &c11, u, v);
+        assert_eq!(bilerp(0.0, 0.0), c00);
+        assert_eq!(bilerp(1.0, 0.0), c10);
+        assert_eq!(bilerp(0.0, 1.0), c01);
+        assert_eq!(bilerp(1.0, 1.0), c11);
+        assert_eq!(bilerp(0.5, 0.5), Vector3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn vector_lerp() {
+        let a = Vector3::new(0.0, 2.0, -4.0);
+        let b = Vector3::new(1.0, 4.0, 4.0);
+        assert_eq!(a.lerp(&b, 0.0), a);
+        assert_eq!(a.lerp(&b, 1.0), b);
+        assert_eq!(a.lerp(&b, 0.5), Vector3::new(0.5, 3.0, 0.0));
+    }
+
+    #[test]
+    fn vector_reflect_twice_is_identity() {
+        let v = Vector3::new(0.3, -0.8, 0.5);
+        let n = Vector3::new(1.0, 2.0, -1.0).normalize();
+        assert!(v.reflect(&n).reflect(&n).approx_eq(&v, 1e-12));
+        assert!((v.reflect(&n) * n + v * n).abs() < 1e-12);
+    }
+
+    #[test]
+    fn vector_refract_obeys_snells_law() {
+        let v = Vector3::new(1.0, -2.0, 0.0).normalize();
+        let n = Vector3::new(0.0, 1.0, 0.0);
+        let refracted = v.refract(&n, 1.0 / 1.5).unwrap();
+        let sin_i = v.cross(&n).norm();
+        let sin_t = refracted.cross(&n).norm();
+        assert!((sin_i - 1.5 * sin_t).abs() < 1e-12);
+        assert!((refracted.norm() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn vector_refract_with_matching_indices_passes_straight_through() {
+        let v = Vector3::new(1.0, -1.0, 0.0).normalize();
+        let n = Vector3::new(0.0, 1.0, 0.0);
+        assert!(v.refract(&n, 1.0).unwrap().approx_eq(&v, 1e-12));
+    }
+
+    #[test]
+    fn vector_refract_total_internal_reflection() {
+        let v = Vector3::new(1.0, -0.2, 0.0).normalize();
+        let n = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(v.refract(&n, 1.5), None);
+    }
+
+    #[test]
+    fn vector_distance() {
+        let a = Vector3::new(1.0, 1.0, 0.0);
+        let b = Vector3::new(4.0, 5.0, 0.0);
+        assert_eq!(a.distance(&b), 5.0);
+        assert_eq!(a.distance_squared(&b), 25.0);
+        assert_eq!(b.distance(&a), 5.0);
+    }
+
+    #[test]
+    fn vector_normalization() {
+        let v = Vector3::new(1.0, 1.0, 1.0);
+        let n = 1.0 / 3.0_f64.sqrt();
+        assert!(v.normalize().approx_eq(&Vector3::new(n, n, n), 1e-12));
+    }
+
+    #[test]
+    fn vector_approx_eq() {
+        let a = Vector3::new(1.0, 2.0, 3.0);
+        let b = a + Vector3::new(1e-12, -1e-12, 0.0);
+        assert!(a.approx_eq(&b, 1e-9));
+        assert!(!a.approx_eq(&b, 1e-15));
+    }
+
+    #[test]
+    fn vector_parses_from_comma_separated_string() {
+        assert_eq!(
+            Vector3::try_from("1.0,2.0,3.0"),
+            Ok(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(
+            Vector3::try_from(" -1, 0.5 ,2e1"),
+            Ok(Vector3::new(-1.0, 0.5, 20.0))
+        );
+    }
+
+    #[test]
+    fn vector_parse_rejects_wrong_component_count() {
+        assert_eq!(
+            Vector3::try_from("1.0,2.0"),
+            Err(ParseVectorError::WrongCount(2))
+        );
+        assert_eq!(
+            Vector3::try_from("1,2,3,4"),
+            Err(ParseVectorError::WrongCount(4))
+        );
+    }
+
+    #[test]
+    fn vector_parse_rejects_garbage() {
+        let err = Vector3::try_from("1.0,abc,3.0").unwrap_err();
+        assert!(matches!(&err, ParseVectorError::InvalidComponent(c, _) if c == "abc"));
+        assert!(err.to_string().contains("\"abc\""));
+        assert!(Vector3::try_from("").is_err());
+    }
+
+    #[test]
+    fn point_reflects_across_plane() {
+        let above = Vector3::new(3.0, 1.0, -2.0);
+        let origin = Vector3::new_zero();
+        let up = Vector3::new(0.0, 2.0, 0.0);
+        assert_eq!(
+            above.reflect_about_plane(&origin, &up),
+            Vector3::new(3.0, -1.0, -2.0)
+        );
+        let plane_point = Vector3::new(5.0, 4.0, 0.0);
+        assert_eq!(
+            above.reflect_about_plane(&plane_point, &up),
+            Vector3::new(3.0, 7.0, -2.0)
+        );
+        assert_eq!(
+            reflect_point(&Vector3::new(0.0, 1.0, 0.0), &origin, &up),
+            Vector3::new(0.0, -1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn vector_clamp01() {
+        let v = Vector3::new(2.0, -1.0, 0.5);
+        assert_eq!(v.clamp01(), Vector3::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn vector_saturate_matches_clamp01() {
+        let v = Vector3::new(2.0, -1.0, 0.5);
+        assert_eq!(v.saturate(), v.clamp01());
+    }
+
+    #[test]
+    fn vector_floor_ceil_and_fract() {
+        let v = Vector3::new(1.7, -0.3, 2.0);
+        assert_eq!(v.floor(), Vector3::new(1.0, -1.0, 2.0));
+        assert_eq!(v.ceil(), Vector3::new(2.0, -0.0, 2.0));
+        assert!(v.fract().approx_eq(&Vector3::new(0.7, 0.7, 0.0), 1e-12));
+    }
+
+    #[test]
+    fn white_has_unit_luminance() {
+        assert!((Vector3::new(1.0, 1.0, 1.0).luminance() - 1.0).abs() < 1e-12);
+        assert_eq!(Vector3::new(0.0, 1.0, 0.0).luminance(), 0.7152);
+    }
+
+    #[test]
+    fn vector_powf() {
+        let v = Vector3::new(0.25, 0.25, 0.25);
+        assert_eq!(v.powf(0.5), Vector3::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn vector_clamp() {
+        let v = Vector3::new(-3.0, 0.5, 7.0);
+        assert_eq!(v.clamp(-1.0, 2.0), Vector3::new(-1.0, 0.5, 2.0));
+    }
+
+    #[test]
+    fn vector_clamp_vec() {
+        let v = Vector3::new(-3.0, 0.5, 7.0);
+        let min = Vector3::new(-2.0, 0.0, 0.0);
+        let max = Vector3::new(2.0, 0.4, 8.0);
+        assert_eq!(v.clamp_vec(&min, &max), Vector3::new(-2.0, 0.4, 7.0));
+    }
+
+    #[test]
+    fn random_hemisphere_samples_face_the_normal() {
+        let normal = Vector3::new(1.0, 2.0, -0.5).normalize();
+        let mut rng = Rng::new(9);
+        for _ in 0..1000 {
+            let v = Vector3::random_in_hemisphere(&normal, &mut rng);
+            assert!((v.norm() - 1.0).abs() < 1e-12);
+            assert!(v * normal >= 0.0);
+        }
+    }
+
+    #[test]
+    fn vector_angle_between() {
+        let x = Vector3::new(2.0, 0.0, 0.0);
+        let y = Vector3::new(0.0, 3.0, 0.0);
+        assert_eq!(x.angle_between(&y), std::f64::consts::FRAC_PI_2);
+        let v = Vector3::new(1.0, 2.0, 2.0);
+        assert_eq!(v.angle_between(&(v * 2.0)), 0.0);
+        assert!((v.angle_between(&-v) - std::f64::consts::PI).abs() < 1e-12);
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn vector_operators_on_references() {
+        let v1 = Vector3::new(1.0, 2.0, 3.0);
+        let v2 = Vector3::new(-4.0, 0.5, 6.0);
+        assert_eq!(&v1 + &v2, v1 + v2);
+        assert_eq!(&v1 - &v2, v1 - v2);
+        assert_eq!(&v1 * 2.5, v1 * 2.5);
+        assert_eq!(-&v1, -v1);
+    }
+
+    #[test]
+    fn vector_projection_and_rejection() {
+        let v = Vector3::new(1.0, 1.0, 0.0);
+        let x_axis = Vector3::new(1.0, 0.0, 0.0);
+        assert_eq!(v.project_onto(&x_axis), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(v.reject_from(&x_axis), Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn vector_round_trips_through_json() {
+        let v = Vector3::new(0.1, -2.5, 1.0 / 3.0);
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, r#"{"x":0.1,"y":-2.5,"z":0.3333333333333333}"#);
+        assert_eq!(serde_json::from_str::<Vector3>(&json).unwrap(), v);
+    }
+
+    #[test]
+    fn vector_is_finite() {
+        assert!(Vector3::new(1.0, -2.0, 3.0).is_finite());
+        assert!(!Vector3::new(1.0, f64::NAN, 0.0).is_finite());
+        assert!(!Vector3::new(f64::INFINITY, 0.0, 0.0).is_finite());
+    }
+
+    #[test]
+    fn vector_min_max() {
+        let a = Vector3::new(1.0, 5.0, 3.0);
+        let b = Vector3::new(4.0, 2.0, 6.0);
+        assert_eq!(a.max(&b), Vector3::new(4.0, 5.0, 6.0));
+        assert_eq!(a.min(&b), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(a.min_component(), 1.0);
+        assert_eq!(a.max_component(), 5.0);
+    }
+
+    #[test]
+    fn vector_normalize_in_place() {
+        let mut v = Vector3::new(3.0, -4.0, 12.0);
+        v.normalize_mut();
+        assert!((v.norm() - 1.0).abs() < 1e-12);
+        assert!(v.approx_eq(&Vector3::new(3.0, -4.0, 12.0).normalize(), 1e-12));
+    }
+
+    #[test]
+    fn vector_try_normalize() {
+        assert_eq!(Vector3::new_zero().try_normalize(), None);
+        assert_eq!(
+            Vector3::new(0.0, 3.0, 4.0).try_normalize(),
+            Some(Vector3::new(0.0, 0.6, 0.8))
+        );
+    }
+
+    #[test]
+    fn zero_vector_normalizes_to_nan() {
+        let n = Vector3::new_zero().normalize();
+        assert!(n.x.is_nan() && n.y.is_nan() && n.z.is_nan());
+    }
+
+    #[test]
+    fn ray_direction_is_normalized() {
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -5.0));
+        assert_eq!(ray.direction, Vector3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn ray_at_zero_is_origin() {
+        let ray = Ray::new(Vector3::new(1.0, 2.0, 3.0), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(ray.at(0.0), Vector3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn ray_at_one_is_origin_plus_direction() {
+        let ray = Ray::new(Vector3::new(1.0, 2.0, 3.0), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(ray.at(1.0), Vector3::new(1.0, 3.0, 3.0));
+    }
+
+    fn unit_sphere_at(center: Vector3) -> Sphere {
+        Sphere::new(center, 1.0, Material::new(Vector3::new(1.0, 1.0, 1.0)))
+    }
+
+    #[test]
+    fn sphere_missed_by_ray() {
+        let sphere = unit_sphere_at(Vector3::new(0.0, 0.0, -5.0));
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(sphere.ray_intersect(&ray), None);
+    }
+
+    #[test]
+    fn sphere_hit_head_on() {
+        let sphere = unit_sphere_at(Vector3::new(0.0, 0.0, -5.0));
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(sphere.ray_intersect(&ray).map(|hit| hit.t), Some(4.0));
+    }
+
+    #[test]
+    fn sphere_hit_from_inside() {
+        let sphere = unit_sphere_at(Vector3::new_zero());
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(sphere.ray_intersect(&ray).map(|hit| hit.t), Some(1.0));
+    }
+
+    #[test]
+    fn sphere_texture_coordinates_follow_latitude() {
+        let sphere = unit_sphere_at(Vector3::new(0.0, 0.0, -5.0));
+        let down = Ray::new(Vector3::new(0.0, 5.0, -5.0), Vector3::new(0.0, -1.0, 0.0));
+        assert_eq!(sphere.ray_intersect(&down).unwrap().v, 0.0);
+        let side = Ray::new(Vector3::new(5.0, 0.0, -5.0), Vector3::new(-1.0, 0.0, 0.0));
+        let hit = sphere.ray_intersect(&side).unwrap();
+        assert_eq!((hit.u, hit.v), (0.75, 0.5));
+    }
+
+    #[test]
+    fn surface_facing_light_gets_full_intensity() {
+        let objects: Vec<Box<dyn Hittable>> =
+            vec![Box::new(unit_sphere_at(Vector3::new(0.0, 0.0, -5.0)))];
+        let lights = vec![Light::new(Vector3::new(0.0, 0.0, 10.0), 1.5)];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            cast_ray(&ray, &objects, &lights, 0),
+            Vector3::new(1.5, 1.5, 1.5)
+        );
+    }
+
+    #[test]
+    fn attenuated_light_falls_off_with_distance_squared() {
+        let objects: Vec<Box<dyn Hittable>> =
+            vec![Box::new(unit_sphere_at(Vector3::new(0.0, 0.0, -5.0)))];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let near = vec![Light::attenuated(Vector3::new(0.0, 0.0, -2.0), 1.0)];
+        let far = vec![Light::attenuated(Vector3::new(0.0, 0.0, 0.0), 1.0)];
+
+        let near = cast_ray(&ray, &objects, &near, 0);
+        let far = cast_ray(&ray, &objects, &far, 0);
+        assert!((near.x / far.x - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn directional_light_does_not_depend_on_position() {
+        let lights = vec![Light::directional(Vector3::new(0.0, 0.0, -1.0), 1.5)];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        for z in [-5.0, -500.0] {
+            let objects: Vec<Box<dyn Hittable>> =
+                vec![Box::new(unit_sphere_at(Vector3::new(0.0, 0.0, z)))];
+            assert_eq!(
+                cast_ray(&ray, &objects, &lights, 0),
+                Vector3::new(1.5, 1.5, 1.5)
+            );
+        }
+    }
+
+    #[test]
+    fn uv_scale_repeats_texture() {
+        let white = Vector3::new(1.0, 1.0, 1.0);
+        let mut material = Material {
+            texture: Texture::Checker {
+                even: white,
+                odd: Vector3::new_zero(),
+                scale: 2.0,
+            },
+            ..Material::default()
+        };
+        let changes = |material: &Material| {
+            (0..100)
+                .map(|k| material.color_at(k as f64 / 100.0, 0.1))
+                .collect::<Vec<_>>()
+                .windows(2)
+                .filter(|pair| pair[0] != pair[1])
+                .count()
+        };
+        assert_eq!(changes(&material), 1);
+        material.uv_scale = 2.0;
+        assert_eq!(changes(&material), 3);
+
+        material.uv_offset = (0.5, 0.0);
+        assert_eq!(material.color_at(0.0, 0.1), Vector3::new_zero());
+    }
+
+    #[test]
+    fn uv_scale_repeats_image_texture() {
+        let pixels: Vec<Vector3> = (0..4).map(|i| Vector3::new(i as f64, 0.0, 0.0)).collect();
+        for filter in [Filter::Nearest, Filter::Bilinear] {
+            let texture = Texture::Image {
+                width: 4,
+                height: 1,
+                pixels: pixels.clone().into(),
+                filter,
+            };
+            let material = Material {
+                texture: texture.clone(),
+                uv_scale: 2.0,
+                ..Material::default()
+            };
+            // each half of the surface shows the whole image
+            for k in 0..50 {
+                let u = k as f64 / 100.0;
+                let expected = texture.sample(2.0 * u, 0.5);
+                assert!(material.color_at(u, 0.5).approx_eq(&expected, 1e-9));
+                assert!(material.color_at(u + 0.5, 0.5).approx_eq(&expected, 1e-9));
+            }
+        }
+    }
+
+    #[test]
+    fn metal_reflects_exactly_without_fuzz() {
+        let incident = Vector3::new(1.0, -2.0, -0.5).normalize();
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        let mut rng = Rng::new(0);
+        let metal = Material::metal(Vector3::new(0.9, 0.6, 0.2), 0.0);
+        assert_eq!(
+            metal.reflect_direction(&incident, &normal, &mut rng),
+            reflect(&incident, &normal).normalize()
+        );
+
+        let rough = Material::metal(Vector3::new(0.9, 0.6, 0.2), 3.0);
+        assert_eq!(rough.fuzz, 1.0);
+        let blurred = rough.reflect_direction(&incident, &normal, &mut rng);
+        assert_ne!(blurred, reflect(&incident, &normal).normalize());
+        assert!((blurred.norm() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn metal_tints_reflections() {
+        let gold = Vector3::new(1.0, 0.8, 0.3);
+        let objects: Vec<Box<dyn Hittable>> = vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            Material::metal(gold, 0.0),
+        ))];
+        // straight back at the camera, into the sky
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let sky = background(
+            &Vector3::new(0.0, 0.0, 1.0),
+            BACKGROUND_TOP,
+            BACKGROUND_BOTTOM,
+        );
+        assert!(cast_ray(&ray, &objects, &[], 0).approx_eq(&gold.componentwise(&sky), 1e-9));
+    }
+
+    #[test]
+    fn dielectric_mostly_refracts_head_on() {
+        let glass = Material::dielectric(1.5);
+        let sphere = Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, glass.clone());
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let hit = sphere.ray_intersect(&ray).unwrap();
+
+        let mut rng = Rng::new(4);
+        let samples = 10_000;
+        let reflected = (0..samples)
+            .filter(|_| {
+                let (scattered, _) = glass.scatter(&ray, &hit, &mut rng).unwrap();
+                scattered.direction * hit.normal > 0.0
+            })
+            .count();
+        let reflectance = reflected as f64 / samples as f64;
+        assert!((reflectance - 0.04).abs() < 0.01, "{}", reflectance);
+    }
+
+    #[test]
+    fn emissive_sphere_glows_without_lights() {
+        let glow = Vector3::new(1.0, 0.6, 0.2);
+        let lamp = Material {
+            emission: glow,
+            ..Material::new(Vector3::new_zero())
+        };
+        let objects: Vec<Box<dyn Hittable>> = vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            lamp,
+        ))];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(cast_ray(&ray, &objects, &[], 0), glow);
+    }
+
+    #[test]
+    fn reflect_at_45_degrees() {
+        let incident = Vector3::new(1.0, 0.0, -1.0).normalize();
+        let normal = Vector3::new(0.0, 0.0, 1.0);
+        let expected = Vector3::new(1.0, 0.0, 1.0).normalize();
+        assert!(reflect(&incident, &normal).approx_eq(&expected, 1e-12));
+    }
+
+    #[test]
+    fn specular_highlight_adds_white() {
+        let shiny = Material {
+            albedo: [0.0, 1.0, 0.0, 0.0],
+            specular_exponent: 10.0,
+            ..Material::new(Vector3::new(1.0, 0.0, 0.0))
+        };
+        let objects: Vec<Box<dyn Hittable>> = vec![Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            shiny,
+        ))];
+        let lights = vec![Light::new(Vector3::new(0.0, 0.0, 10.0), 1.0)];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            cast_ray(&ray, &objects, &lights, 0),
+            Vector3::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn reflect_flips_normal_component() {
+        let incident = Vector3::new(1.0, -1.0, 0.0);
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(reflect(&incident, &normal), Vector3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn reflect_leaves_perpendicular_vector_unchanged() {
+        let incident = Vector3::new(1.0, 0.0, 2.0);
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(reflect(&incident, &normal), incident);
+    }
+
+    #[test]
+    fn sphere_between_light_and_surface_casts_shadow() {
+        let target = unit_sphere_at(Vector3::new(0.0, 0.0, -5.0));
+        let blocker = unit_sphere_at(Vector3::new(0.0, 5.0, -4.0));
+        let lights = vec![Light::new(Vector3::new(0.0, 10.0, -4.0), 1.0)];
+        let ray = Ray::new(Vector3::new(0.0, 0.5, 0.0), Vector3::new(0.0, 0.0, -1.0));
+
+        let lit = cast_ray(
+            &ray,
+            &[Box::new(target.clone()) as Box<dyn Hittable>],
+            &lights,
+            0,
+        );
+        let shadowed = cast_ray(
+            &ray,
+            &[Box::new(target) as Box<dyn Hittable>, Box::new(blocker)],
+            &lights,
+            0,
+        );
+        assert!(shadowed.x < lit.x);
+    }
+
+    #[test]
+    fn hit_from_inside_sphere_faces_inwards() {
+        let sphere = Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, Material::default());
+        let outside = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let hit = sphere.ray_intersect(&outside).unwrap();
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+
+        let inside = Ray::new(Vector3::new(0.0, 0.0, -5.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = sphere.ray_intersect(&inside).unwrap();
+        assert!(!hit.front_face);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(hit.relative_index(), 1.0);
+    }
+
+    #[test]
+    fn spotlight_fades_from_axis_to_outer_cone() {
+        let spot = Light::spot(
+            Vector3::new(0.0, 5.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+            2.0,
+            0.2,
+            0.4,
+        );
+        assert_eq!(spot.intensity_at(&Vector3::new(0.0, 0.0, 0.0)), 2.0);
+        // 45 degrees off the axis, well past the outer cone
+        assert_eq!(spot.intensity_at(&Vector3::new(5.0, 0.0, 0.0)), 0.0);
+        // 0.3 radians off the axis, halfway between the cones
+        let edge = spot.intensity_at(&Vector3::new(5.0 * 0.3f64.tan(), 0.0, 0.0));
+        assert!(edge > 0.0 && edge < 2.0);
+        assert_eq!(
+            spot.direction_from(&Vector3::new_zero()),
+            (Vector3::new(0.0, 1.0, 0.0), 5.0)
+        );
+    }
+
+    #[test]
+    fn black_material_absorbs_scattered_light() {
+        let hit = HitRecord {
+            t: 1.0,
+            point: Vector3::new_zero(),
+            normal: Vector3::new(0.0, 1.0, 0.0),
+            front_face: true,
+            object_id: None,
+            material: Material::new(Vector3::new_zero()),
+            u: 0.0,
+            v: 0.0,
+        };
+        let ray = Ray::new(Vector3::new(0.0, 1.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+        let (scattered, attenuation) = hit.material.scatter(&ray, &hit, &mut Rng::new(3)).unwrap();
+        assert_eq!(attenuation, Vector3::new_zero());
+        assert!(scattered.direction * hit.normal >= 0.0);
+    }
+
+    #[test]
+    fn material_presets() {
+        assert!(Material::mirror().albedo[2] >= 0.8);
+        assert!((Material::glass().refractive_index - 1.5).abs() < 1e-9);
+        assert_eq!(Material::default().albedo, [1.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn mirror_shows_color_of_sphere_it_reflects() {
+        let mirror = Material {
+            albedo: [0.0, 0.0, 1.0, 0.0],
+            ..Material::new(Vector3::new(1.0, 1.0, 1.0))
+        };
+        let red = Material::new(Vector3::new(1.0, 0.0, 0.0));
+        let objects: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, mirror)),
+            Box::new(Sphere::new(Vector3::new(0.0, 0.0, 5.0), 1.0, red)),
+        ];
+        let lights = vec![Light::new(Vector3::new_zero(), 1.0)];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+
+        let color = cast_ray(&ray, &objects, &lights, 0);
+        assert!(color.approx_eq(&Vector3::new(1.0, 0.0, 0.0), 1e-9));
+    }
+
+    #[test]
+    fn refract_through_flat_interface() {
+        // 45 degrees from air into a medium of index sqrt(2) bends to 30 degrees
+        let incident = Vector3::new(1.0, -1.0, 0.0).normalize();
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        let refracted = refract(&incident, &normal, 2.0_f64.sqrt(), 1.0);
+        let expected = Vector3::new(0.5, -(3.0_f64.sqrt() / 2.0), 0.0);
+        assert!(refracted.approx_eq(&expected, 1e-9));
+    }
+
+    #[test]
+    fn refract_total_internal_reflection_mirrors() {
+        // leaving glass at 60 degrees is past the ~41.8 degree critical angle
+        let incident = Vector3::new(3.0_f64.sqrt() / 2.0, 0.5, 0.0);
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        let refracted = refract(&incident, &normal, 1.5, 1.0);
+        assert_eq!(refracted, reflect(&incident, &normal));
+    }
+
+    #[test]
+    fn fresnel_head_on_glass_reflects_four_percent() {
+        let incident = Vector3::new(0.0, -1.0, 0.0);
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        assert!((fresnel(&incident, &normal, 1.5) - 0.04).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fresnel_grows_towards_grazing_and_total_internal_reflection() {
+        let normal = Vector3::new(0.0, 1.0, 0.0);
+        let grazing = Vector3::new(1.0, -0.01, 0.0).normalize();
+        assert!(fresnel(&grazing, &normal, 1.5) > 0.9);
+        let leaving = Vector3::new(3.0_f64.sqrt() / 2.0, 0.5, 0.0);
+        assert_eq!(fresnel(&leaving, &normal, 1.5), 1.0);
+    }
+
+    #[test]
+    fn ray_pointing_down_hits_ground_plane() {
+        let plane = Plane::new(
+            Vector3::new_zero(),
+            Vector3::new(0.0, 1.0, 0.0),
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        );
+        let ray = Ray::new(Vector3::new(2.0, 3.0, -1.0), Vector3::new(0.0, -1.0, 0.0));
+        assert_eq!(plane.ray_intersect(&ray).map(|hit| hit.t), Some(3.0));
+    }
+
+    #[test]
+    fn checkerboard_alternates_between_adjacent_squares() {
+        let white = Vector3::new(1.0, 1.0, 1.0);
+        let black = Vector3::new_zero();
+        let plane = Plane::checkerboard(
+            Vector3::new_zero(),
+            Vector3::new(0.0, 1.0, 0.0),
+            Material::new(white),
+            black,
+        );
+        assert_eq!(
+            plane
+                .material_at(&Vector3::new(0.5, 0.0, 0.5))
+                .color_at(0.0, 0.0),
+            white
+        );
+        assert_eq!(
+            plane
+                .material_at(&Vector3::new(1.5, 0.0, 0.5))
+                .color_at(0.0, 0.0),
+            black
+        );
+        assert_eq!(
+            plane
+                .material_at(&Vector3::new(-0.5, 0.0, 0.5))
+                .color_at(0.0, 0.0),
+            black
+        );
+        assert_eq!(
+            plane
+                .material_at(&Vector3::new(-0.5, 0.0, -0.5))
+                .color_at(0.0, 0.0),
+            white
+        );
+    }
+
+    #[test]
+    fn uv_scale_shrinks_checkerboard_squares() {
+        let mut plane = Plane::checkerboard(
+            Vector3::new_zero(),
+            Vector3::new(0.0, 1.0, 0.0),
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+            Vector3::new_zero(),
+        );
+        // widths of the runs of equal color crossed walking along x
+        let tile_widths = |plane: &Plane| {
+            let colors: Vec<_> = (0..400)
+                .map(|k| {
+                    let x = (k as f64 + 0.5) / 100.0;
+                    plane
+                        .material_at(&Vector3::new(x, 0.0, 0.5))
+                        .color_at(0.0, 0.0)
+                })
+                .collect();
+            let mut widths = vec![];
+            let mut run = 1;
+            for pair in colors.windows(2) {
+                if pair[0] == pair[1] {
+                    run += 1;
+                } else {
+                    widths.push(run);
+                    run = 1;
+                }
+            }
+            widths
+        };
+        assert_eq!(tile_widths(&plane), vec![100; 3]);
+
+        plane.material.uv_scale = 2.0;
+        assert_eq!(tile_widths(&plane), vec![50; 7]);
+
+        plane.material.uv_offset = (0.5, 0.0);
+        assert_eq!(tile_widths(&plane)[0], 25);
+    }
+
+    #[test]
+    fn scene_intersect_returns_nearest_object() {
+        let near = Material::new(Vector3::new(1.0, 0.0, 0.0));
+        let far = Material::new(Vector3::new(0.0, 0.0, 1.0));
+        let objects: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Sphere::new(Vector3::new(0.0, 0.0, -10.0), 1.0, far)),
+            Box::new(Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, near.clone())),
+        ];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+
+        let hit = scene_intersect(&ray, &objects, f64::INFINITY).unwrap();
+        assert_eq!(hit.t, 4.0);
+        assert_eq!(hit.material, near);
+    }
+
+    #[test]
+    fn intersects_arrays_of_unboxed_objects() {
+        let spheres = [
+            unit_sphere_at(Vector3::new(0.0, 0.0, -10.0)),
+            unit_sphere_at(Vector3::new(0.0, 0.0, -5.0)),
+        ];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(
+            scene_intersect(&ray, &spheres, f64::INFINITY).unwrap().t,
+            4.0
+        );
+        assert!(scene_intersect(&ray, &spheres[..1], 5.0).is_none());
+    }
+
+    #[test]
+    fn far_objects_are_cut_off_at_max_distance() {
+        let objects: Vec<Box<dyn Hittable>> =
+            vec![Box::new(unit_sphere_at(Vector3::new(0.0, 0.0, -2000.0)))];
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert!(scene_intersect(&ray, &objects, f64::INFINITY).is_some());
+        assert!(scene_intersect(&ray, &objects, 1000.0).is_none());
+    }
+
+    #[test]
+    fn sphere_accessors_and_bounding_box() {
+        let sphere = Sphere::new(Vector3::new_zero(), 2.0, Material::glass());
+        assert_eq!(sphere.center(), Vector3::new_zero());
+        assert_eq!(sphere.radius(), 2.0);
+        assert_eq!(sphere.material(), &Material::glass());
+
+        let bbox = sphere.bounding_box().unwrap();
+        assert_eq!(bbox.min, Vector3::new(-2.0, -2.0, -2.0));
+        assert_eq!(bbox.max, Vector3::new(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn background_gradient_ends() {
+        let top = Vector3::new(0.0, 0.0, 1.0);
+        let bottom = Vector3::new(1.0, 1.0, 1.0);
+        assert_eq!(background(&Vector3::new(0.0, 1.0, 0.0), top, bottom), top);
+        assert_eq!(
+            background(&Vector3::new(0.0, -1.0, 0.0), top, bottom),
+            bottom
+        );
+    }
+
+    #[test]
+    fn missed_ray_returns_background_gradient() {
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(cast_ray(&ray, &[], &[], 0), BACKGROUND_TOP);
+    }
+}
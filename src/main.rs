@@ -0,0 +1,331 @@
+use std::f64::consts::PI;
+use std::io;
+use std::process;
+
+use tiny_rusty_raytracer::{
+    load_scene, post_process, render_framebuffer_with_progress, with_threads, write_png,
+    write_ppm_binary, Background, BitDepth, BvhNode, Camera, EnvironmentMap, Hittable, PostProcess,
+    RenderMode, Scene, SceneDescription, Timer, Toggle, ToneMap, Vector3,
+};
+
+const USAGE: &str = "usage: tiny-rusty-raytracer [--width N] [--height N] [--fov RADIANS] \
+                     [--exposure STOPS] [--vignette 0..1] [--tone-map none|reinhard] \
+                     [--bit-depth 8|16] \
+                     [--mode shaded|normals|depth|albedo] [--env IMAGE] [--threads N] \
+                     [--out PATH] [SCENE]";
+
+/// Distance at which `--mode depth` fades to black, far enough to take in the
+/// default scene.
+const DEPTH_FAR: f64 = 30.0;
+
+/// Prints info and more severe log messages, such as how long each stage of
+/// the render took, to stderr.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    width: usize,
+    height: usize,
+    fov: f64,
+    post_process: PostProcess,
+    tone_map: ToneMap,
+    bit_depth: BitDepth,
+    mode: RenderMode,
+    environment: Option<String>,
+    /// Threads to render on; all cores when `None`.
+    threads: Option<usize>,
+    out: String,
+    scene: Option<String>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            width: 1024,
+            height: 768,
+            fov: PI / 2.0,
+            post_process: PostProcess::default(),
+            tone_map: ToneMap::None,
+            bit_depth: BitDepth::Eight,
+            mode: RenderMode::Shaded,
+            environment: None,
+            threads: None,
+            out: String::from("out.ppm"),
+            scene: None,
+        }
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "--width" => options.width = parse_dimension("--width", &value("--width")?)?,
+            "--height" => options.height = parse_dimension("--height", &value("--height")?)?,
+            "--fov" => {
+                let fov = value("--fov")?;
+                options.fov = fov
+                    .parse()
+                    .ok()
+                    .filter(|fov| *fov > 0.0 && *fov < PI)
+                    .ok_or_else(|| format!("--fov must be in (0, π) radians, got {}", fov))?;
+            }
+            "--exposure" => {
+                let exposure = value("--exposure")?;
+                options.post_process.exposure = exposure
+                    .parse()
+                    .ok()
+                    .filter(|exposure: &f64| exposure.is_finite())
+                    .ok_or_else(|| format!("--exposure must be a number, got {}", exposure))?;
+            }
+            "--vignette" => {
+                let vignette = value("--vignette")?;
+                options.post_process.vignette = Some(
+                    vignette
+                        .parse()
+                        .ok()
+                        .filter(|vignette| (0.0..=1.0).contains(vignette))
+                        .ok_or_else(|| format!("--vignette must be in [0, 1], got {}", vignette))?,
+                );
+            }
+            "--tone-map" => {
+                options.tone_map = match value("--tone-map")?.as_str() {
+                    "none" => ToneMap::None,
+                    "reinhard" => ToneMap::Reinhard,
+                    other => return Err(format!("unknown tone map {}", other)),
+                }
+            }
+            "--bit-depth" => {
+                options.bit_depth = match value("--bit-depth")?.as_str() {
+                    "8" => BitDepth::Eight,
+                    "16" => BitDepth::Sixteen,
+                    other => return Err(format!("--bit-depth must be 8 or 16, got {}", other)),
+                }
+            }
+            "--mode" => {
+                options.mode = match value("--mode")?.as_str() {
+                    "shaded" => RenderMode::Shaded,
+                    "normals" => RenderMode::Normals,
+                    "depth" => RenderMode::Depth {
+                        near: 0.0,
+                        far: DEPTH_FAR,
+                    },
+                    "albedo" => RenderMode::Albedo,
+                    other => return Err(format!("unknown render mode {}", other)),
+                }
+            }
+            "--env" => options.environment = Some(value("--env")?),
+            "--threads" => {
+                options.threads = Some(parse_dimension("--threads", &value("--threads")?)?)
+            }
+            "--out" => options.out = value("--out")?,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ if options.scene.is_none() => options.scene = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+fn parse_dimension(name: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("{} must be a positive integer, got {}", name, value))
+}
+
+fn render(scene: &Scene, options: &Options) -> io::Result<()> {
+    let camera = Camera::new(
+        Vector3::new_zero(),
+        options.fov,
+        options.width,
+        options.height,
+    );
+    let mut last_pct = None;
+    let mut framebuffer = with_threads(options.threads, || {
+        render_framebuffer_with_progress(scene, &camera, |fraction| {
+            let pct = (fraction * 100.0) as u32;
+            if last_pct != Some(pct) {
+                eprint!("\rrendered {}%", pct);
+                last_pct = Some(pct);
+            }
+            if fraction >= 1.0 {
+                // end the progress line before the render's own log line
+                eprintln!();
+            }
+        })
+    })
+    .unwrap_or_else(|err| {
+        eprintln!("error: could not start render threads: {}", err);
+        process::exit(1);
+    });
+    post_process(
+        &mut framebuffer,
+        options.width,
+        options.height,
+        &options.post_process,
+    );
+    let framebuffer: Vec<Vector3> = framebuffer
+        .iter()
+        .map(|color| options.tone_map.apply(color))
+        .collect();
+
+    if options.out.ends_with(".png") {
+        write_png(
+            &options.out,
+            &framebuffer,
+            options.width,
+            options.height,
+            options.bit_depth,
+        )
+    } else {
+        write_ppm_binary(
+            &options.out,
+            &framebuffer,
+            options.width,
+            options.height,
+            options.bit_depth,
+        )
+    }
+}
+
+fn main() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+    let options = parse_args(std::env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("error: {}\n{}", err, USAGE);
+        process::exit(2);
+    });
+
+    let timer = Timer::start();
+    let scene = match &options.scene {
+        Some(path) => load_scene(path).unwrap_or_else(|err| {
+            eprintln!("error: {}: {}", path, err);
+            process::exit(1);
+        }),
+        None => SceneDescription::default(),
+    };
+
+    let mut scene = scene.to_scene();
+    scene.render_mode = options.mode;
+    if let Some(path) = &options.environment {
+        scene.background =
+            Background::Environment(EnvironmentMap::load(path).unwrap_or_else(|err| {
+                eprintln!("error: {}: {}", path, err);
+                process::exit(1);
+            }));
+    }
+
+    log::info!("set up scene in {:.0} ms", timer.elapsed_ms());
+
+    // one tree over every object, so that rays skip the ones they can't hit;
+    // switched-off objects go in too and are still never hit
+    let objects: Vec<Box<dyn Hittable>> = std::mem::take(&mut scene.objects)
+        .into_iter()
+        .map(|object| Box::new(object) as Box<dyn Hittable>)
+        .collect();
+    scene.objects = vec![Toggle::new(Box::new(BvhNode::build(objects)))];
+
+    if let Err(err) = render(&scene, &options) {
+        eprintln!("error: could not write {}: {}", options.out, err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn no_arguments_give_defaults() {
+        assert_eq!(parse_args(args(&[])), Ok(Options::default()));
+    }
+
+    #[test]
+    fn flags_and_scene_path() {
+        let options = parse_args(args(&[
+            "--width",
+            "64",
+            "scene.json",
+            "--height",
+            "48",
+            "--fov",
+            "1.0",
+            "--exposure",
+            "-1.5",
+            "--vignette",
+            "0.3",
+            "--tone-map",
+            "reinhard",
+            "--bit-depth",
+            "16",
+            "--mode",
+            "normals",
+            "--env",
+            "sky.png",
+            "--threads",
+            "2",
+            "--out",
+            "a.png",
+        ]))
+        .unwrap();
+        assert_eq!(
+            options,
+            Options {
+                width: 64,
+                height: 48,
+                fov: 1.0,
+                post_process: PostProcess {
+                    exposure: -1.5,
+                    vignette: Some(0.3),
+                },
+                tone_map: ToneMap::Reinhard,
+                bit_depth: BitDepth::Sixteen,
+                mode: RenderMode::Normals,
+                environment: Some(String::from("sky.png")),
+                threads: Some(2),
+                out: String::from("a.png"),
+                scene: Some(String::from("scene.json")),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        assert!(parse_args(args(&["--width", "0"])).is_err());
+        assert!(parse_args(args(&["--height", "-3"])).is_err());
+        assert!(parse_args(args(&["--fov", "3.2"])).is_err());
+        assert!(parse_args(args(&["--fov"])).is_err());
+        assert!(parse_args(args(&["--threads", "0"])).is_err());
+        assert!(parse_args(args(&["--exposure", "bright"])).is_err());
+        assert!(parse_args(args(&["--vignette", "1.5"])).is_err());
+        assert!(parse_args(args(&["--depth", "3"])).is_err());
+        assert!(parse_args(args(&["--tone-map", "aces"])).is_err());
+        assert!(parse_args(args(&["--bit-depth", "12"])).is_err());
+        assert!(parse_args(args(&["--mode", "wireframe"])).is_err());
+    }
+}
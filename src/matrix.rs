@@ -0,0 +1,172 @@
+use std::ops;
+
+use crate::Vector3;
+
+/// A 4x4 row-major matrix for affine transforms of points and vectors,
+/// applied to column vectors: `a * b` transforms by `b` first, then `a`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Matrix4 {
+        Matrix4 { m }
+    }
+
+    pub fn identity() -> Matrix4 {
+        Matrix4::scale(Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn translation(offset: Vector3) -> Matrix4 {
+        Matrix4::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scale(factors: Vector3) -> Matrix4 {
+        Matrix4::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counter-clockwise rotation by `angle` radians, looking down the x
+    /// axis towards the origin.
+    pub fn rotation_x(angle: f64) -> Matrix4 {
+        let (sin, cos) = angle.sin_cos();
+        Matrix4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, -sin, 0.0],
+            [0.0, sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_y(angle: f64) -> Matrix4 {
+        let (sin, cos) = angle.sin_cos();
+        Matrix4::new([
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rotation_z(angle: f64) -> Matrix4 {
+        let (sin, cos) = angle.sin_cos();
+        Matrix4::new([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transform_point(&self, p: &Vector3) -> Vector3 {
+        self.transform_vector(p) + Vector3::new(self.m[0][3], self.m[1][3], self.m[2][3])
+    }
+
+    /// Transforms a direction, which unlike a point is unaffected by
+    /// translation.
+    pub fn transform_vector(&self, v: &Vector3) -> Vector3 {
+        let row = |r: &[f64; 4]| r[0] * v.x + r[1] * v.y + r[2] * v.z;
+        Vector3::new(row(&self.m[0]), row(&self.m[1]), row(&self.m[2]))
+    }
+
+    pub fn transpose(&self) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Matrix4::new(m)
+    }
+
+    /// The matrix undoing this transform, found by Gauss-Jordan elimination,
+    /// or `None` if it is singular.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mut a = self.m;
+        let mut inv = Matrix4::identity().m;
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for k in 0..4 {
+                a[col][k] *= scale;
+                inv[col][k] *= scale;
+            }
+            for row in (0..4).filter(|&row| row != col) {
+                let factor = a[row][col];
+                for k in 0..4 {
+                    a[row][k] -= factor * a[col][k];
+                    inv[row][k] -= factor * inv[col][k];
+                }
+            }
+        }
+        Some(Matrix4::new(inv))
+    }
+}
+
+impl ops::Mul<Matrix4> for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, other: Matrix4) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * other.m[k][j]).sum();
+            }
+        }
+        Matrix4::new(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn translation_moves_points_but_not_vectors() {
+        let t = Matrix4::translation(Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            t.transform_point(&Vector3::new_zero()),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            t.transform_vector(&Vector3::new(0.0, 1.0, 0.0)),
+            Vector3::new(0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn x_rotation_maps_y_to_z() {
+        let r = Matrix4::rotation_x(FRAC_PI_2);
+        let rotated = r.transform_vector(&Vector3::new(0.0, 1.0, 0.0));
+        assert!(rotated.approx_eq(&Vector3::new(0.0, 0.0, 1.0), 1e-12));
+    }
+
+    #[test]
+    fn inverse_undoes_transform() {
+        let m = Matrix4::translation(Vector3::new(1.0, -2.0, 3.0))
+            * Matrix4::rotation_y(0.7)
+            * Matrix4::scale(Vector3::new(2.0, 3.0, 0.5));
+        let p = Vector3::new(0.3, -1.2, 4.0);
+        let back = m.inverse().unwrap().transform_point(&m.transform_point(&p));
+        assert!(back.approx_eq(&p, 1e-12));
+        assert_eq!(Matrix4::scale(Vector3::new_zero()).inverse(), None);
+    }
+}
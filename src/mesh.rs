@@ -0,0 +1,148 @@
+use crate::triangle::intersect_triangle;
+use crate::{Aabb, HitRecord, Hittable, Material, Ray, Vector3};
+
+/// Triangles indexing into a shared list of vertices, all of one material.
+/// Each face's corners are in counter-clockwise order when seen from the side
+/// its normal faces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mesh {
+    pub vertices: Vec<Vector3>,
+    pub indices: Vec<[usize; 3]>,
+    /// Vertex normals for smooth shading, indexed by `normal_indices`; both
+    /// are empty for a faceted mesh.
+    pub normals: Vec<Vector3>,
+    /// The normal of each corner, one entry per face in `indices`.
+    pub normal_indices: Vec<[usize; 3]>,
+    pub material: Material,
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vector3>, indices: Vec<[usize; 3]>, material: Material) -> Mesh {
+        Mesh {
+            vertices,
+            indices,
+            normals: Vec::new(),
+            normal_indices: Vec::new(),
+            material,
+        }
+    }
+
+    /// Shades the mesh smoothly by interpolating `normals` across each face.
+    pub fn with_normals(self, normals: Vec<Vector3>, normal_indices: Vec<[usize; 3]>) -> Mesh {
+        Mesh {
+            normals,
+            normal_indices,
+            ..self
+        }
+    }
+
+    fn corners(&self, face: &[usize; 3]) -> [Vector3; 3] {
+        face.map(|index| self.vertices[index])
+    }
+}
+
+impl Hittable for Mesh {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        let mut nearest: Option<(f64, f64, f64, usize)> = None;
+        for (k, face) in self.indices.iter().enumerate() {
+            let [v0, v1, v2] = self.corners(face);
+            if let Some((t, u, v)) = intersect_triangle(ray, &v0, &v1, &v2) {
+                if nearest.is_none_or(|(nearest_t, ..)| t < nearest_t) {
+                    nearest = Some((t, u, v, k));
+                }
+            }
+        }
+        // the material is only cloned for the face that was actually hit
+        let (t, u, v, k) = nearest?;
+        let outward = match self.normal_indices.get(k) {
+            Some(corners) => {
+                let [n0, n1, n2] = corners.map(|index| self.normals[index]);
+                (n0 * (1.0 - u - v) + n1 * u + n2 * v).normalize()
+            }
+            None => {
+                let [v0, v1, v2] = self.corners(&self.indices[k]);
+                (v1 - v0).cross(&(v2 - v0)).normalize()
+            }
+        };
+        let (front_face, normal) = HitRecord::face_normal(ray, outward);
+        Some(HitRecord {
+            t,
+            point: ray.at(t),
+            normal,
+            front_face,
+            object_id: None,
+            material: self.material.clone(),
+            u,
+            v,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.indices
+            .iter()
+            .flat_map(|face| self.corners(face))
+            .map(|corner| Aabb::new(corner, corner))
+            .reduce(|a, b| a.surrounding(&b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad() -> Mesh {
+        Mesh::new(
+            vec![
+                Vector3::new(-1.0, -1.0, -5.0),
+                Vector3::new(1.0, -1.0, -5.0),
+                Vector3::new(1.0, 1.0, -5.0),
+                Vector3::new(-1.0, 1.0, -5.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn central_ray_hits_quad() {
+        let ray = Ray::new(Vector3::new(0.1, 0.2, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = quad().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 5.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+
+        let beside = Ray::new(Vector3::new(1.5, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(quad().ray_intersect(&beside), None);
+    }
+
+    #[test]
+    fn smooth_normal_is_interpolated() {
+        let mesh = Mesh::new(
+            vec![
+                Vector3::new(-1.0, -1.0, -5.0),
+                Vector3::new(1.0, -1.0, -5.0),
+                Vector3::new(0.0, 2.0, -5.0),
+            ],
+            vec![[0, 1, 2]],
+            Material::default(),
+        )
+        .with_normals(
+            vec![
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(0.0, 0.0, 1.0),
+            ],
+            vec![[0, 1, 2]],
+        );
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let hit = mesh.ray_intersect(&ray).unwrap();
+        let average = Vector3::new(1.0, 1.0, 1.0).normalize();
+        assert!(hit.normal.approx_eq(&average, 1e-9));
+    }
+
+    #[test]
+    fn bounding_box_spans_faces() {
+        let bbox = quad().bounding_box().unwrap();
+        assert_eq!(bbox.min, Vector3::new(-1.0, -1.0, -5.0));
+        assert_eq!(bbox.max, Vector3::new(1.0, 1.0, -5.0));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{intersect_sphere, Aabb, HitRecord, Hittable, Material, Ray, Vector3};
+
+/// A sphere moving in a straight line from `center0` at `time0` to
+/// `center1` at `time1`, which blurs in renders with motion blur.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovingSphere {
+    pub center0: Vector3,
+    pub center1: Vector3,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: Material,
+}
+
+impl MovingSphere {
+    pub fn new(
+        center0: Vector3,
+        center1: Vector3,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: Material,
+    ) -> MovingSphere {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
+        }
+    }
+
+    /// Where the center is at `time`; the sphere keeps moving at the same
+    /// speed outside [`time0`, `time1`].
+    pub fn center(&self, time: f64) -> Vector3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        let t = (time - self.time0) / (self.time1 - self.time0);
+        self.center0.lerp(&self.center1, t)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        intersect_sphere(self.center(ray.time), self.radius, &self.material, ray)
+    }
+
+    /// A box holding the sphere everywhere it goes while the shutter is
+    /// open, for ray times from 0 to 1.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vector3::new(self.radius, self.radius, self.radius);
+        let (start, end) = (self.center(0.0), self.center(1.0));
+        let start = Aabb::new(start - r, start + r);
+        let end = Aabb::new(end - r, end + r);
+        Some(start.surrounding(&end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sphere() -> MovingSphere {
+        MovingSphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            Vector3::new(2.0, 0.0, -5.0),
+            0.0,
+            1.0,
+            0.5,
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn center_moves_between_endpoints() {
+        let sphere = sphere();
+        assert_eq!(sphere.center(0.0), sphere.center0);
+        assert_eq!(sphere.center(1.0), sphere.center1);
+        assert_eq!(sphere.center(0.5), Vector3::new(1.0, 0.0, -5.0));
+    }
+
+    #[test]
+    fn box_covers_the_whole_shutter_interval() {
+        // moving only in the middle of the shutter interval, so it is
+        // further along at time 0 and 1 than at its endpoints
+        let sphere = MovingSphere {
+            time0: 0.25,
+            time1: 0.75,
+            ..sphere()
+        };
+        let bbox = sphere.bounding_box().unwrap();
+        assert_eq!(bbox.min.x, -1.0 - 0.5);
+        assert_eq!(bbox.max.x, 3.0 + 0.5);
+
+        let late = Ray::new(Vector3::new(3.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let bvh = crate::BvhNode::build(vec![Box::new(sphere.clone())]);
+        assert!(sphere.ray_intersect(&late.with_time(0.99)).is_some());
+        assert!(bvh.ray_intersect(&late.with_time(0.99)).is_some());
+    }
+
+    #[test]
+    fn rays_hit_the_sphere_where_it_is_at_their_time() {
+        let sphere = sphere();
+        let at_start = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let at_end = Ray::new(Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(sphere.ray_intersect(&at_start).unwrap().t, 4.5);
+        assert_eq!(sphere.ray_intersect(&at_end), None);
+        assert_eq!(sphere.ray_intersect(&at_end.with_time(1.0)).unwrap().t, 4.5);
+        assert_eq!(sphere.ray_intersect(&at_start.with_time(1.0)), None);
+    }
+}
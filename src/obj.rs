@@ -0,0 +1,154 @@
+use std::fs;
+use std::io;
+
+use crate::{Material, Mesh, Vector3};
+
+/// Loads the faces of a Wavefront OBJ file as a mesh of `material`.
+pub fn load_obj(path: &str, material: Material) -> io::Result<Mesh> {
+    parse_obj(&fs::read_to_string(path)?, material)
+}
+
+/// Parses OBJ `v`, `vn` and `f` statements, fanning polygons into
+/// triangles. The mesh is shaded smoothly when every face corner names a
+/// normal. Texture coordinates and every other statement are ignored.
+pub fn parse_obj(source: &str, material: Material) -> io::Result<Mesh> {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut normals = Vec::new();
+    // `None` once a face without normals turns up
+    let mut normal_indices = Some(Vec::new());
+
+    for (line_number, line) in source.lines().enumerate() {
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", line_number + 1, message),
+            )
+        };
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some(keyword @ ("v" | "vn")) => {
+                let coords = tokens
+                    .take(3)
+                    .map(|token| token.parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| invalid("malformed vertex"))?;
+                if coords.len() != 3 {
+                    return Err(invalid("vertex needs three coordinates"));
+                }
+                let coords = Vector3::new(coords[0], coords[1], coords[2]);
+                if keyword == "v" {
+                    vertices.push(coords);
+                } else {
+                    normals.push(coords);
+                }
+            }
+            Some("f") => {
+                let tokens: Vec<&str> = tokens.collect();
+                let corners = tokens
+                    .iter()
+                    .map(|token| resolve_index(token.split('/').next()?, vertices.len()))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| invalid("malformed face"))?;
+                if corners.len() < 3 {
+                    return Err(invalid("face needs at least three vertices"));
+                }
+                let corner_normals = tokens
+                    .iter()
+                    .map(|token| match token.split('/').nth(2) {
+                        Some(index) => resolve_index(index, normals.len()).map(Some),
+                        None => Some(None),
+                    })
+                    .collect::<Option<Option<Vec<_>>>>()
+                    .ok_or_else(|| invalid("malformed face normal"))?;
+                for k in 1..corners.len() - 1 {
+                    indices.push([corners[0], corners[k], corners[k + 1]]);
+                }
+                normal_indices = normal_indices.zip(corner_normals).map(|(mut faces, n)| {
+                    for k in 1..n.len() - 1 {
+                        faces.push([n[0], n[k], n[k + 1]]);
+                    }
+                    faces
+                });
+            }
+            _ => {} // comments, blank lines and unsupported statements
+        }
+    }
+
+    let mesh = Mesh::new(vertices, indices, material);
+    Ok(match normal_indices {
+        Some(normal_indices) if !normals.is_empty() => mesh.with_normals(normals, normal_indices),
+        _ => mesh,
+    })
+}
+
+/// Turns an OBJ index such as `3` or `-1` into an index into the `count`
+/// vertices or normals read so far.
+fn resolve_index(token: &str, count: usize) -> Option<usize> {
+    let index: i64 = token.parse().ok()?;
+    let index = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    if (0..count as i64).contains(&index) {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn material() -> Material {
+        Material::new(Vector3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn parses_single_triangle() {
+        let source = "# a triangle\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let mesh = parse_obj(source, material()).unwrap();
+        assert_eq!(
+            mesh,
+            Mesh::new(
+                vec![
+                    Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(1.0, 0.0, 0.0),
+                    Vector3::new(0.0, 1.0, 0.0),
+                ],
+                vec![[0, 1, 2]],
+                material(),
+            )
+        );
+    }
+
+    #[test]
+    fn fans_quads_and_resolves_relative_indices() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf -4/1 -3/2 -2/3 -1/4\n";
+        let mesh = parse_obj(source, material()).unwrap();
+        assert_eq!(mesh.indices, vec![[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn reads_vertex_normals() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nvn 0 1 0\nf 1//1 2//2 3//1\n";
+        let mesh = parse_obj(source, material()).unwrap();
+        assert_eq!(
+            mesh.normals,
+            vec![Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 1.0, 0.0)]
+        );
+        assert_eq!(mesh.normal_indices, vec![[0, 1, 0]]);
+
+        let flat = parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n", material()).unwrap();
+        assert!(flat.normal_indices.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_faces() {
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nf 1 2\n", material()).is_err());
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n", material()).is_err());
+        assert!(parse_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 x 3\n", material()).is_err());
+    }
+}
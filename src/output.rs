@@ -0,0 +1,324 @@
+use std::fs::File;
+use std::io::{self, Write};
+
+use image::{ExtendedColorType, ImageError};
+
+use crate::Vector3;
+
+const GAMMA: f64 = 2.2;
+
+/// How linear colors brighter than 1 are squeezed into the displayable range
+/// before gamma correction.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMap {
+    /// Leave colors alone; anything above 1 clips to white.
+    #[default]
+    None,
+    /// [`tone_map_reinhard`].
+    Reinhard,
+}
+
+impl ToneMap {
+    pub fn apply(&self, color: &Vector3) -> Vector3 {
+        match self {
+            ToneMap::None => *color,
+            ToneMap::Reinhard => tone_map_reinhard(color),
+        }
+    }
+}
+
+/// Adjustments to a rendered image, made in linear color before tone
+/// mapping. The default changes nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PostProcess {
+    /// Brightness change in stops: colors are scaled by 2^`exposure`.
+    pub exposure: f64,
+    /// How dark the corners get, from 0 for not at all to 1 for black, with
+    /// the image darkening gradually away from its center.
+    pub vignette: Option<f64>,
+}
+
+/// Applies `settings` to a `width` by `height` framebuffer in place.
+pub fn post_process(buffer: &mut [Vector3], width: usize, height: usize, settings: &PostProcess) {
+    let scale = settings.exposure.exp2();
+    for (index, pixel) in buffer.iter_mut().enumerate() {
+        let mut factor = scale;
+        if let Some(strength) = settings.vignette {
+            // squared distance from the center, 0 there and 1 in the corners
+            let x = 2.0 * ((index % width) as f64 + 0.5) / width as f64 - 1.0;
+            let y = 2.0 * ((index / width) as f64 + 0.5) / height as f64 - 1.0;
+            factor *= 1.0 - strength * (x * x + y * y) / 2.0;
+        }
+        *pixel *= factor;
+    }
+}
+
+/// Bits per channel in written images.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BitDepth {
+    #[default]
+    Eight,
+    /// Keeps smooth gradients from banding, at twice the size.
+    Sixteen,
+}
+
+impl BitDepth {
+    /// The value standing for full intensity.
+    pub fn max_value(&self) -> u16 {
+        match self {
+            BitDepth::Eight => 255,
+            BitDepth::Sixteen => 65535,
+        }
+    }
+
+    /// A pixel's channels in this depth, as in [`color_to_rgb8`].
+    fn encode(&self, color: &Vector3) -> [u16; 3] {
+        let max = self.max_value() as f64;
+        let corrected = color.saturate().powf(1.0 / GAMMA);
+        [corrected.x, corrected.y, corrected.z].map(|c| {
+            if c.is_nan() {
+                return 0;
+            }
+            (max * c).round() as u16
+        })
+    }
+
+    /// A pixel as big-endian bytes, the layout binary PPMs use.
+    fn to_be_bytes(self, color: &Vector3) -> Vec<u8> {
+        let channels = self.encode(color);
+        match self {
+            BitDepth::Eight => channels.iter().map(|&c| c as u8).collect(),
+            BitDepth::Sixteen => channels.iter().flat_map(|c| c.to_be_bytes()).collect(),
+        }
+    }
+}
+
+/// Maps each channel `c` to `c / (1 + c)`, which keeps dark colors almost
+/// unchanged and brings any brightness below 1.
+pub fn tone_map_reinhard(color: &Vector3) -> Vector3 {
+    Vector3::new(
+        color.x / (1.0 + color.x),
+        color.y / (1.0 + color.y),
+        color.z / (1.0 + color.z),
+    )
+}
+
+/// Clamps a linear color to [0, 1], gamma-corrects it and scales it to
+/// bytes. NaN channels become 0 so a bad pixel can't wrap around.
+pub fn color_to_rgb8(color: &Vector3) -> [u8; 3] {
+    BitDepth::Eight.encode(color).map(|c| c as u8)
+}
+
+/// Writes the framebuffer as an ASCII (P3) PPM.
+pub fn write_ppm(
+    path: &str,
+    framebuffer: &[Vector3],
+    width: usize,
+    height: usize,
+    bit_depth: BitDepth,
+) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "P3\n{} {}\n{}", width, height, bit_depth.max_value())?;
+    for pixel in framebuffer {
+        let [r, g, b] = bit_depth.encode(pixel);
+        writeln!(file, "{} {} {}", r, g, b)?;
+    }
+    Ok(())
+}
+
+/// Writes the framebuffer as a binary (P6) PPM, a fraction of the size of the
+/// ASCII variant.
+pub fn write_ppm_binary(
+    path: &str,
+    framebuffer: &[Vector3],
+    width: usize,
+    height: usize,
+    bit_depth: BitDepth,
+) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    write!(
+        file,
+        "P6\n{} {}\n{}\n",
+        width,
+        height,
+        bit_depth.max_value()
+    )?;
+    let bytes: Vec<u8> = framebuffer
+        .iter()
+        .flat_map(|pixel| bit_depth.to_be_bytes(pixel))
+        .collect();
+    file.write_all(&bytes)
+}
+
+/// Encodes the framebuffer as an RGB PNG.
+pub fn write_png(
+    path: &str,
+    framebuffer: &[Vector3],
+    width: usize,
+    height: usize,
+    bit_depth: BitDepth,
+) -> io::Result<()> {
+    // `image` takes 16-bit samples in native byte order
+    let (bytes, color_type): (Vec<u8>, _) = match bit_depth {
+        BitDepth::Eight => (
+            framebuffer.iter().flat_map(color_to_rgb8).collect(),
+            ExtendedColorType::Rgb8,
+        ),
+        BitDepth::Sixteen => (
+            framebuffer
+                .iter()
+                .flat_map(|pixel| bit_depth.encode(pixel))
+                .flat_map(u16::to_ne_bytes)
+                .collect(),
+            ExtendedColorType::Rgb16,
+        ),
+    };
+
+    image::save_buffer(path, &bytes, width as u32, height as u32, color_type).map_err(|err| {
+        match err {
+            ImageError::IoError(err) => err,
+            err => io::Error::other(err),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gamma_corrects_and_clamps() {
+        assert_eq!(color_to_rgb8(&Vector3::new(0.0, 1.0, 0.5)), [0, 255, 186]);
+        assert_eq!(
+            color_to_rgb8(&Vector3::new(-1.0, 3.0, f64::NAN)),
+            [0, 255, 0]
+        );
+    }
+
+    #[test]
+    fn reinhard_compresses_highlights_and_keeps_shadows() {
+        let bright = tone_map_reinhard(&Vector3::new(100.0, 1000.0, 10.0));
+        assert!(bright.max_component() < 1.0);
+        let dark = Vector3::new(0.01, 0.001, 0.0);
+        assert!(tone_map_reinhard(&dark).approx_eq(&dark, 1e-4));
+        assert_eq!(ToneMap::None.apply(&bright), bright);
+    }
+
+    #[test]
+    fn zero_exposure_changes_nothing() {
+        let original = vec![Vector3::new(0.2, 0.5, 3.0); 6];
+        let mut buffer = original.clone();
+        post_process(&mut buffer, 3, 2, &PostProcess::default());
+        assert_eq!(buffer, original);
+
+        let brighter = PostProcess {
+            exposure: 1.0,
+            ..PostProcess::default()
+        };
+        post_process(&mut buffer, 3, 2, &brighter);
+        assert_eq!(buffer[0], Vector3::new(0.4, 1.0, 6.0));
+    }
+
+    #[test]
+    fn vignette_darkens_corners_more_than_center() {
+        let (width, height) = (9, 7);
+        let mut buffer = vec![Vector3::new(1.0, 1.0, 1.0); width * height];
+        let settings = PostProcess {
+            vignette: Some(0.5),
+            ..PostProcess::default()
+        };
+        post_process(&mut buffer, width, height, &settings);
+
+        let center = buffer[height / 2 * width + width / 2];
+        assert_eq!(center, Vector3::new(1.0, 1.0, 1.0));
+        for corner in [0, width - 1, (height - 1) * width, height * width - 1] {
+            assert!(buffer[corner].x < center.x);
+            assert!(buffer[corner].x > 0.5);
+        }
+    }
+
+    #[test]
+    fn ppm_lists_one_line_per_pixel() {
+        let path = std::env::temp_dir().join("tiny_rusty_raytracer_write_ppm.ppm");
+        let path = path.to_str().unwrap();
+        let framebuffer = [Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0)];
+
+        write_ppm(path, &framebuffer, 2, 1, BitDepth::Eight).unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(contents, "P3\n2 1\n255\n255 0 0\n0 0 255\n");
+    }
+
+    #[test]
+    fn sixteen_bit_ppm_uses_full_range() {
+        let path = std::env::temp_dir().join("tiny_rusty_raytracer_write_ppm_16.ppm");
+        let path = path.to_str().unwrap();
+        let framebuffer = [Vector3::new(1.0, 0.0, 0.5)];
+
+        write_ppm(path, &framebuffer, 1, 1, BitDepth::Sixteen).unwrap();
+        let contents = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(contents, "P3\n1 1\n65535\n65535 0 47824\n");
+    }
+
+    #[test]
+    fn binary_ppm_has_header_and_three_bytes_per_pixel() {
+        let path = std::env::temp_dir().join("tiny_rusty_raytracer_write_ppm_binary.ppm");
+        let path = path.to_str().unwrap();
+        let framebuffer = vec![Vector3::new(1.0, 0.0, 1.0); 4 * 3];
+
+        write_ppm_binary(path, &framebuffer, 4, 3, BitDepth::Eight).unwrap();
+        let contents = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let header = b"P6\n4 3\n255\n";
+        assert!(contents.starts_with(header));
+        let pixels = &contents[header.len()..];
+        assert_eq!(pixels.len(), 4 * 3 * 3);
+        assert_eq!(&pixels[..3], &[255, 0, 255]);
+    }
+
+    #[test]
+    fn writing_to_invalid_path_is_an_error() {
+        let path = "/nonexistent-directory/out.ppm";
+        let framebuffer = [Vector3::new_zero()];
+        let depth = BitDepth::default();
+        assert!(write_ppm(path, &framebuffer, 1, 1, depth).is_err());
+        assert!(write_ppm_binary(path, &framebuffer, 1, 1, depth).is_err());
+        assert!(write_png("/nonexistent-directory/out.png", &framebuffer, 1, 1, depth).is_err());
+    }
+
+    #[test]
+    fn png_round_trip_clamps_channels() {
+        let path = std::env::temp_dir().join("tiny_rusty_raytracer_write_png.png");
+        let path = path.to_str().unwrap();
+        let framebuffer = [
+            Vector3::new(1.0, 0.0, 0.5),
+            Vector3::new(2.0, -1.0, f64::NAN),
+        ];
+
+        write_png(path, &framebuffer, 2, 1, BitDepth::Eight).unwrap();
+        let image = image::open(path).unwrap().to_rgb8();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 186]);
+        assert_eq!(image.get_pixel(1, 0).0, [255, 0, 0]);
+    }
+
+    #[test]
+    fn sixteen_bit_png_round_trip() {
+        let path = std::env::temp_dir().join("tiny_rusty_raytracer_write_png_16.png");
+        let path = path.to_str().unwrap();
+        let framebuffer = [Vector3::new(1.0, 0.0, 0.5)];
+
+        write_png(path, &framebuffer, 1, 1, BitDepth::Sixteen).unwrap();
+        let image = image::open(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(image.color(), image::ColorType::Rgb16);
+        assert_eq!(image.to_rgb16().get_pixel(0, 0).0, [65535, 0, 47824]);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Aabb, HitRecord, Hittable, Material, Ray, Vector3};
+
+/// A flat parallelogram with one corner at `origin` and sides `u` and `v`,
+/// facing `u × v`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quad {
+    pub origin: Vector3,
+    pub u: Vector3,
+    pub v: Vector3,
+    pub material: Material,
+}
+
+impl Quad {
+    pub fn new(origin: Vector3, u: Vector3, v: Vector3, material: Material) -> Quad {
+        Quad {
+            origin,
+            u,
+            v,
+            material,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        let n = self.u.cross(&self.v);
+        let outward = n.try_normalize()?;
+        let denom = outward * ray.direction;
+        if denom.abs() < 1e-6 {
+            return None;
+        }
+        let t = (self.origin - ray.origin) * outward / denom;
+        if t < 0.0 {
+            return None;
+        }
+        // coordinates of the hit along the sides, from 0 to 1 inside the quad
+        let point = ray.at(t);
+        let p = point - self.origin;
+        let w = n / n.length_squared();
+        let a = w * p.cross(&self.v);
+        let b = w * self.u.cross(&p);
+        if !(0.0..=1.0).contains(&a) || !(0.0..=1.0).contains(&b) {
+            return None;
+        }
+        let (front_face, normal) = HitRecord::face_normal(ray, outward);
+        Some(HitRecord {
+            t,
+            point,
+            normal,
+            front_face,
+            object_id: None,
+            material: self.material.clone(),
+            u: a,
+            v: b,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let opposite = self.origin + self.u + self.v;
+        let diagonal = Aabb::new(self.origin.min(&opposite), self.origin.max(&opposite));
+        let (side_u, side_v) = (self.origin + self.u, self.origin + self.v);
+        Some(diagonal.surrounding(&Aabb::new(side_u.min(&side_v), side_u.max(&side_v))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad() -> Quad {
+        Quad::new(
+            Vector3::new(-1.0, -1.0, -5.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn ray_hits_inside_quad() {
+        let ray = Ray::new(Vector3::new(0.5, 0.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = quad().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 5.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!((hit.u, hit.v), (0.75, 0.5));
+    }
+
+    #[test]
+    fn ray_misses_just_past_corner() {
+        let ray = Ray::new(Vector3::new(1.01, 1.01, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(quad().ray_intersect(&ray), None);
+        let ray = Ray::new(Vector3::new(0.99, 0.99, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert!(quad().ray_intersect(&ray).is_some());
+    }
+
+    #[test]
+    fn bounding_box_holds_all_corners() {
+        let quad = Quad::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(-1.0, 0.0, 2.0),
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        );
+        let bbox = quad.bounding_box().unwrap();
+        assert_eq!(bbox.min, Vector3::new(-1.0, 0.0, 0.0));
+        assert_eq!(bbox.max, Vector3::new(1.0, 1.0, 2.0));
+    }
+}
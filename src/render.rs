@@ -0,0 +1,398 @@
+use std::fs;
+use std::io;
+use std::ops::ControlFlow;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rayon::prelude::*;
+
+use crate::{write_png, BitDepth, Camera, Rng, Scene, Timer, Vector3};
+
+/// Traces `camera.samples_per_pixel` primary rays per pixel, returning the
+/// pixels row by row from the top-left corner. Each pixel draws its jitter
+/// from its own RNG seeded by `camera.seed` and the pixel index, so the image
+/// doesn't depend on how rayon schedules the work.
+pub fn render_framebuffer(scene: &Scene, camera: &Camera) -> Vec<Vector3> {
+    render_framebuffer_with_progress(scene, camera, |_| {})
+}
+
+/// Runs `render` on a thread pool of its own with `threads` threads, or on
+/// rayon's global pool, using every core, when `None`. Renders come out the
+/// same whatever the thread count. Fails if the threads can't be started.
+pub fn with_threads<R: Send>(
+    threads: Option<usize>,
+    render: impl FnOnce() -> R + Send,
+) -> Result<R, rayon::ThreadPoolBuildError> {
+    let Some(threads) = threads else {
+        return Ok(render());
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()?;
+    Ok(pool.install(render))
+}
+
+/// Renders `scene` into a framebuffer without touching any files, for
+/// callers that want the pixels themselves; hand the result to
+/// [`write_png`](crate::write_png) or a PPM writer to save it.
+pub fn render_to_buffer(scene: &Scene, camera: &Camera) -> Vec<Vector3> {
+    render_framebuffer(scene, camera)
+}
+
+/// Like [`render_framebuffer`], calling `on_progress` with the fraction of
+/// rows completed so far, in [0, 1], after each row. Counts overtaken by a
+/// later one while waiting to be reported are skipped, so the fractions only
+/// ever go up. How long the render took is logged at info level.
+pub fn render_framebuffer_with_progress(
+    scene: &Scene,
+    camera: &Camera,
+    on_progress: impl FnMut(f64) + Send,
+) -> Vec<Vector3> {
+    let timer = Timer::start();
+    let width = camera.width;
+    if width == 0 {
+        return Vec::new();
+    }
+    let mut framebuffer = vec![Vector3::new_zero(); width * camera.height];
+    let rows_done = AtomicUsize::new(0);
+    // the highest count reported so far, alongside the callback
+    let progress = Mutex::new((0, on_progress));
+
+    framebuffer
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(j, row)| {
+            for (i, pixel) in row.iter_mut().enumerate() {
+                *pixel = render_pixel(scene, camera, i, j);
+            }
+
+            let done = rows_done.fetch_add(1, Ordering::Relaxed) + 1;
+            let mut progress = progress.lock().unwrap();
+            let (reported, on_progress) = &mut *progress;
+            if done > *reported {
+                *reported = done;
+                on_progress(done as f64 / camera.height as f64);
+            }
+        });
+
+    log_render_time(camera, &timer);
+    framebuffer
+}
+
+/// A rectangle of the image, `width` by `height` pixels with its top-left
+/// corner at pixel (`x`, `y`), and its colors row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Vector3>,
+}
+
+/// Like [`render_framebuffer`], but working through square tiles of
+/// `tile_size` pixels (smaller along the right and bottom edges), which keeps
+/// each thread on a compact part of the scene. `on_tile` sees every tile as
+/// it completes, and can stop the render early by breaking; tiles not yet
+/// started are then left black. How long the render took is logged at info
+/// level.
+pub fn render_tiled(
+    scene: &Scene,
+    camera: &Camera,
+    tile_size: usize,
+    on_tile: impl FnMut(&Tile) -> ControlFlow<()> + Send,
+) -> Vec<Vector3> {
+    let timer = Timer::start();
+    let tile_size = tile_size.max(1);
+    let corners: Vec<(usize, usize)> = (0..camera.height)
+        .step_by(tile_size)
+        .flat_map(|y| (0..camera.width).step_by(tile_size).map(move |x| (x, y)))
+        .collect();
+    let on_tile = Mutex::new(on_tile);
+    let cancelled = AtomicBool::new(false);
+
+    let tiles: Vec<Tile> = corners
+        .into_par_iter()
+        .filter_map(|(x, y)| {
+            if cancelled.load(Ordering::Relaxed) {
+                return None;
+            }
+            let width = tile_size.min(camera.width - x);
+            let height = tile_size.min(camera.height - y);
+            let pixels = (y..y + height)
+                .flat_map(|j| (x..x + width).map(move |i| (i, j)))
+                .map(|(i, j)| render_pixel(scene, camera, i, j))
+                .collect();
+            let tile = Tile {
+                x,
+                y,
+                width,
+                height,
+                pixels,
+            };
+            if (on_tile.lock().unwrap())(&tile).is_break() {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            Some(tile)
+        })
+        .collect();
+
+    let mut framebuffer = vec![Vector3::new_zero(); camera.width * camera.height];
+    for tile in tiles {
+        for (row, pixels) in tile.pixels.chunks(tile.width).enumerate() {
+            let start = (tile.y + row) * camera.width + tile.x;
+            framebuffer[start..start + tile.width].copy_from_slice(pixels);
+        }
+    }
+    log_render_time(camera, &timer);
+    framebuffer
+}
+
+fn log_render_time(camera: &Camera, timer: &Timer) {
+    log::info!(
+        "rendered {}x{} in {:.0} ms",
+        camera.width,
+        camera.height,
+        timer.elapsed_ms()
+    );
+}
+
+/// Renders an animation of `frames` frames into `out_dir`, creating it if
+/// needed, as `frame_0001.png` onwards. Frame `n` shows the scene and camera
+/// `frame_fn` builds for `t` going evenly from 0 for the first frame to 1
+/// for the last. Numbers are padded to at least 4 digits, and to the same
+/// width for every frame.
+pub fn render_sequence(
+    frame_fn: impl Fn(f64) -> (Scene, Camera),
+    frames: usize,
+    out_dir: &str,
+) -> io::Result<()> {
+    fs::create_dir_all(out_dir)?;
+    let digits = frames.to_string().len().max(4);
+    for frame in 0..frames {
+        let t = if frames > 1 {
+            frame as f64 / (frames - 1) as f64
+        } else {
+            0.0
+        };
+        let (scene, camera) = frame_fn(t);
+        let framebuffer = render_framebuffer(&scene, &camera);
+        let name = format!("frame_{:0digits$}.png", frame + 1, digits = digits);
+        let path = Path::new(out_dir).join(name);
+        write_png(
+            &path.display().to_string(),
+            &framebuffer,
+            camera.width,
+            camera.height,
+            BitDepth::Eight,
+        )?;
+    }
+    Ok(())
+}
+
+/// Color of pixel (`i`, `j`). Each pixel draws its jitter from its own RNG
+/// seeded by `camera.seed` and the pixel index, so the image doesn't depend
+/// on the order pixels are rendered in.
+fn render_pixel(scene: &Scene, camera: &Camera, i: usize, j: usize) -> Vector3 {
+    let mut rng = Rng::new(camera.seed ^ (i + j * camera.width) as u64);
+    let color = camera.sample_pixel(i, j, &mut rng, |ray, rng| scene.trace(ray, 0, rng));
+    // one degenerate ray shouldn't leave a garbage pixel behind
+    if color.is_finite() {
+        color
+    } else {
+        Vector3::new_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Light, Material, Ray, Sphere};
+
+    #[test]
+    fn matches_serial_render() {
+        let scene = Scene::new(
+            vec![Box::new(Sphere::new(
+                Vector3::new(0.5, 0.0, -5.0),
+                1.5,
+                Material::new(Vector3::new(0.8, 0.2, 0.2)),
+            ))],
+            vec![Light::new(Vector3::new(-5.0, 5.0, 5.0), 1.0)],
+        );
+        let (width, height, fov) = (16, 12, std::f64::consts::PI / 2.0);
+        let camera = Camera::new(Vector3::new_zero(), fov, width, height);
+
+        let mut serial = Vec::new();
+        for j in 0..height {
+            for i in 0..width {
+                let x = (2.0 * (i as f64 + 0.5) / width as f64 - 1.0)
+                    * (fov / 2.0).tan()
+                    * width as f64
+                    / height as f64;
+                let y = -(2.0 * (j as f64 + 0.5) / height as f64 - 1.0) * (fov / 2.0).tan();
+                let ray = Ray::new(Vector3::new_zero(), Vector3::new(x, y, -1.0));
+                serial.push(scene.trace(&ray, 0, &mut Rng::new(0)));
+            }
+        }
+
+        assert_eq!(render_framebuffer(&scene, &camera), serial);
+    }
+
+    #[test]
+    fn buffer_holds_sphere_color_in_center() {
+        let mut material = Material::new(Vector3::new(0.2, 0.4, 0.6));
+        material.albedo = [0.0; 4];
+        material.emission = Vector3::new(0.2, 0.4, 0.6);
+        let scene = Scene::new(
+            vec![Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -3.0),
+                1.0,
+                material,
+            ))],
+            vec![],
+        );
+        let camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 5, 3);
+
+        let buffer = render_to_buffer(&scene, &camera);
+        assert_eq!(buffer.len(), 5 * 3);
+        assert_eq!(buffer[5 + 2], Vector3::new(0.2, 0.4, 0.6));
+    }
+
+    #[test]
+    fn multisampled_render_is_reproducible() {
+        let scene = Scene::new(
+            vec![Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -3.0),
+                1.0,
+                Material::new(Vector3::new(0.8, 0.2, 0.2)),
+            ))],
+            vec![Light::new(Vector3::new(-5.0, 5.0, 5.0), 1.0)],
+        );
+        let mut camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 8, 8);
+        camera.samples_per_pixel = 4;
+
+        assert_eq!(
+            render_framebuffer(&scene, &camera),
+            render_framebuffer(&scene, &camera)
+        );
+    }
+
+    #[test]
+    fn non_finite_colors_are_written_black() {
+        let scene = Scene::new(
+            vec![Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -3.0),
+                1.0,
+                Material::new(Vector3::new(f64::NAN, 0.0, 0.0)),
+            ))],
+            vec![Light::new(Vector3::new(-5.0, 5.0, 5.0), 1.0)],
+        );
+        let camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 9, 9);
+
+        let framebuffer = render_framebuffer(&scene, &camera);
+        assert!(framebuffer.iter().all(Vector3::is_finite));
+        assert_eq!(framebuffer[4 * 9 + 4], Vector3::new_zero());
+    }
+
+    #[test]
+    fn thread_count_does_not_change_the_image() {
+        let mut scene = crate::SceneDescription::default().to_scene();
+        scene.path_tracing = true;
+        let mut camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 24, 16);
+        camera.samples_per_pixel = 2;
+
+        let single = with_threads(Some(1), || render_framebuffer(&scene, &camera)).unwrap();
+        let four = with_threads(Some(4), || render_framebuffer(&scene, &camera)).unwrap();
+        assert_eq!(single, four);
+        assert_eq!(
+            single,
+            with_threads(None, || render_framebuffer(&scene, &camera)).unwrap()
+        );
+    }
+
+    #[test]
+    fn tiled_render_matches_scanline_render() {
+        let scene = crate::SceneDescription::default().to_scene();
+        let mut camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 40, 25);
+        camera.samples_per_pixel = 2;
+
+        let mut tiles = 0;
+        let tiled = render_tiled(&scene, &camera, 16, |tile| {
+            assert_eq!(tile.pixels.len(), tile.width * tile.height);
+            tiles += 1;
+            ControlFlow::Continue(())
+        });
+        assert_eq!(tiles, 3 * 2);
+        assert_eq!(
+            tiled,
+            render_tiled(&scene, &camera, 40, |_| ControlFlow::Continue(()))
+        );
+        assert_eq!(tiled, render_framebuffer(&scene, &camera));
+    }
+
+    #[test]
+    fn breaking_stops_starting_new_tiles() {
+        let scene = crate::SceneDescription::default().to_scene();
+        let camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 64, 64);
+
+        let mut tiles = 0;
+        render_tiled(&scene, &camera, 1, |_| {
+            tiles += 1;
+            ControlFlow::Break(())
+        });
+        assert!(tiles < 64 * 64);
+    }
+
+    #[test]
+    fn sequence_writes_numbered_frames() {
+        let out_dir = std::env::temp_dir().join("tiny_rusty_raytracer_render_sequence");
+        let _ = fs::remove_dir_all(&out_dir);
+        let mut times = Mutex::new(Vec::new());
+
+        render_sequence(
+            |t| {
+                times.lock().unwrap().push(t);
+                let camera = Camera::new(Vector3::new(t, 0.0, 0.0), 1.0, 4, 3);
+                (Scene::new(vec![], vec![]), camera)
+            },
+            3,
+            out_dir.to_str().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(times.get_mut().unwrap(), &[0.0, 0.5, 1.0]);
+        let mut names: Vec<String> = fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["frame_0001.png", "frame_0002.png", "frame_0003.png"]
+        );
+        fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn progress_reaches_one() {
+        let camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 4, 5);
+        let mut reported = Vec::new();
+        render_framebuffer_with_progress(&Scene::new(vec![], vec![]), &camera, |fraction| {
+            reported.push(fraction)
+        });
+
+        assert!(!reported.is_empty() && reported.len() <= 5);
+        assert!(reported.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(reported.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn zero_width_renders_nothing() {
+        let mut camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 4, 5);
+        camera.width = 0;
+        let framebuffer =
+            render_framebuffer_with_progress(&Scene::new(vec![], vec![]), &camera, |_| {});
+        assert!(framebuffer.is_empty());
+    }
+}
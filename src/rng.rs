@@ -0,0 +1,83 @@
+use crate::Vector3;
+
+/// A small, fast xorshift64* generator. Unlike `rand`'s standard generators
+/// its output is fixed by this crate, so a seed always renders the same image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // scramble the seed with a splitmix64 step, so that nearby seeds give
+        // unrelated streams and zero doesn't get xorshift stuck
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Rng {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        // the top 53 bits fill an f64 mantissa exactly
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform inside the unit disk, as (x, y), by rejection sampling.
+    pub fn random_in_unit_disk(&mut self) -> (f64, f64) {
+        loop {
+            let (x, y) = (2.0 * self.next_f64() - 1.0, 2.0 * self.next_f64() - 1.0);
+            if x * x + y * y < 1.0 {
+                return (x, y);
+            }
+        }
+    }
+
+    /// Uniform inside the unit sphere, by rejection sampling.
+    pub fn random_in_unit_sphere(&mut self) -> Vector3 {
+        loop {
+            let p = Vector3::new(
+                2.0 * self.next_f64() - 1.0,
+                2.0 * self.next_f64() - 1.0,
+                2.0 * self.next_f64() - 1.0,
+            );
+            if p.length_squared() < 1.0 {
+                return p;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_stream() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_f64(), b.next_f64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn samples_stay_in_range() {
+        let mut rng = Rng::new(0);
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+            assert!(rng.random_in_unit_sphere().norm() < 1.0);
+        }
+    }
+}
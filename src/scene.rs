@@ -0,0 +1,1027 @@
+#[cfg(feature = "serde")]
+use std::error::Error;
+#[cfg(feature = "serde")]
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs;
+#[cfg(feature = "serde")]
+use std::io;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::background::DEFAULT_BACKGROUND;
+use crate::{
+    fresnel, reflect, refract, scene_intersect, Background, Camera, HitRecord, Hittable, Light,
+    Material, Plane, Ray, Rng, Sphere, Toggle, Vector3, MAX_DEPTH,
+};
+
+/// Everything a render needs to know about the world: the objects to hit and
+/// the lights that shade them. Each can be switched off through its
+/// [`Toggle`] to leave it out of a render without removing it.
+pub struct Scene {
+    pub objects: Vec<Toggle<Box<dyn Hittable>>>,
+    pub lights: Vec<Toggle<Light>>,
+    /// Light reaching every surface from all around, so that nothing is
+    /// completely black. Zero by default.
+    pub ambient: Vector3,
+    /// Whether diffuse surfaces also gather light bounced off other objects,
+    /// by following one randomly scattered ray per hit. Off by default.
+    pub path_tracing: bool,
+    /// Shadow rays cast towards random points of each spherical light, to
+    /// estimate how much of it is hidden.
+    pub shadow_samples: usize,
+    /// What rays that miss every object see; a sky gradient by default.
+    pub background: Background,
+    /// Anything further than this from a ray's origin is ignored. Unlimited
+    /// by default.
+    pub max_distance: f64,
+    pub render_mode: RenderMode,
+    /// Rays cast from each hit to estimate how much of the ambient light
+    /// nearby geometry blocks, darkening creases and contact points. 0, the
+    /// default, turns ambient occlusion off.
+    pub ambient_occlusion_samples: usize,
+    /// How far away geometry still occludes.
+    pub ao_radius: f64,
+}
+
+const DEFAULT_SHADOW_SAMPLES: usize = 16;
+/// Bounces after which path tracing may end a path by Russian roulette.
+const ROULETTE_DEPTH: usize = 3;
+/// Hard limit on path tracing bounces, for paths roulette never ends.
+const MAX_PATH_DEPTH: usize = 64;
+const DEFAULT_AO_RADIUS: f64 = 1.0;
+
+impl Scene {
+    pub fn new(objects: Vec<Box<dyn Hittable>>, lights: Vec<Light>) -> Scene {
+        Scene {
+            objects: objects.into_iter().map(Toggle::new).collect(),
+            lights: lights.into_iter().map(Toggle::new).collect(),
+            ambient: Vector3::new_zero(),
+            path_tracing: false,
+            shadow_samples: DEFAULT_SHADOW_SAMPLES,
+            background: Background::default(),
+            max_distance: f64::INFINITY,
+            render_mode: RenderMode::Shaded,
+            ambient_occlusion_samples: 0,
+            ao_radius: DEFAULT_AO_RADIUS,
+        }
+    }
+
+    /// Adds `object`, for building scenes up in a chain starting from
+    /// `Scene::new(vec![], vec![])`.
+    pub fn add_object(mut self, object: impl Hittable + 'static) -> Scene {
+        self.objects.push(Toggle::new(Box::new(object)));
+        self
+    }
+
+    pub fn add_light(mut self, light: Light) -> Scene {
+        self.lights.push(Toggle::new(light));
+        self
+    }
+
+    pub fn with_background(mut self, background: Background) -> Scene {
+        self.background = background;
+        self
+    }
+
+    pub fn with_ambient(mut self, ambient: Vector3) -> Scene {
+        self.ambient = ambient;
+        self
+    }
+
+    /// The nearest hit along `ray` on an enabled object, if any.
+    pub fn intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        self.tracer().intersect(ray, self.max_distance)
+    }
+
+    /// What is seen through the center of pixel (`i`, `j`) of `camera`, for
+    /// picking objects in a viewer: the nearest hit, whose `object_id` is
+    /// its index in `objects`, or `None` for the background.
+    pub fn pick(&self, camera: &Camera, i: usize, j: usize) -> Option<HitRecord> {
+        self.intersect(&camera.ray_for_pixel(i, j))
+    }
+
+    /// Color seen along `ray`, which has already bounced `depth` times.
+    /// Random choices, like where diffuse bounces go, are drawn from `rng`.
+    pub fn trace(&self, ray: &Ray, depth: usize, rng: &mut Rng) -> Vector3 {
+        self.tracer().trace(ray, depth, rng)
+    }
+
+    fn tracer(&self) -> Tracer<'_, Toggle<Box<dyn Hittable>>> {
+        Tracer {
+            ambient: self.ambient,
+            path_tracing: self.path_tracing,
+            shadow_samples: self.shadow_samples,
+            background: &self.background,
+            max_distance: self.max_distance,
+            render_mode: self.render_mode,
+            ambient_occlusion_samples: self.ambient_occlusion_samples,
+            ao_radius: self.ao_radius,
+            ..Tracer::new(&self.objects, &self.lights)
+        }
+    }
+}
+
+/// What a render shows: the lit scene, or one of the views that help debug
+/// geometry and materials. Rays that miss are black in all but `Shaded`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RenderMode {
+    #[default]
+    Shaded,
+    /// Surface normals, mapped from [-1, 1] to [0, 1] per channel.
+    Normals,
+    /// Gray fading from white for hits at distance `near` or closer to
+    /// black at `far` and beyond. With `far` not past `near` there is no
+    /// fade: hits up to `near` are white and the rest black.
+    Depth { near: f64, far: f64 },
+    /// Base color of the surface, unlit.
+    Albedo,
+}
+
+/// What tracing needs from a [`Scene`], borrowed so that [`crate::cast_ray`]
+/// can trace objects and lights it doesn't own.
+pub(crate) struct Tracer<'a, O> {
+    objects: &'a [O],
+    lights: &'a [Toggle<Light>],
+    ambient: Vector3,
+    path_tracing: bool,
+    shadow_samples: usize,
+    background: &'a Background,
+    max_distance: f64,
+    render_mode: RenderMode,
+    ambient_occlusion_samples: usize,
+    ao_radius: f64,
+}
+
+impl<'a, O: Hittable> Tracer<'a, O> {
+    /// A tracer with the same defaults as [`Scene::new`].
+    pub(crate) fn new(objects: &'a [O], lights: &'a [Toggle<Light>]) -> Tracer<'a, O> {
+        Tracer {
+            objects,
+            lights,
+            ambient: Vector3::new_zero(),
+            path_tracing: false,
+            shadow_samples: DEFAULT_SHADOW_SAMPLES,
+            background: &DEFAULT_BACKGROUND,
+            max_distance: f64::INFINITY,
+            render_mode: RenderMode::Shaded,
+            ambient_occlusion_samples: 0,
+            ao_radius: DEFAULT_AO_RADIUS,
+        }
+    }
+
+    /// The nearest hit on an enabled object closer than `max_distance`.
+    fn intersect(&self, ray: &Ray, max_distance: f64) -> Option<HitRecord> {
+        scene_intersect(ray, self.objects, max_distance)
+    }
+
+    pub(crate) fn trace(&self, ray: &Ray, depth: usize, rng: &mut Rng) -> Vector3 {
+        self.trace_path(ray, depth, Vector3::new(1.0, 1.0, 1.0), rng)
+    }
+
+    /// Like [`Tracer::trace`], for a ray whose color will be scaled by
+    /// `throughput` on its way back to the camera, so that Russian roulette
+    /// can tell how much the rest of the path still matters.
+    fn trace_path(&self, ray: &Ray, depth: usize, throughput: Vector3, rng: &mut Rng) -> Vector3 {
+        // diffuse bounces are ended by Russian roulette instead, so path
+        // tracing only needs a generous cap in case a path never dies
+        let max_depth = if self.path_tracing {
+            MAX_PATH_DEPTH
+        } else {
+            MAX_DEPTH
+        };
+        let hit = if depth > max_depth {
+            None
+        } else {
+            self.intersect(ray, self.max_distance)
+        };
+        let Some(hit) = hit else {
+            if self.render_mode != RenderMode::Shaded {
+                return Vector3::new_zero();
+            }
+            return self.background.color(&ray.direction);
+        };
+        match self.render_mode {
+            RenderMode::Shaded => {}
+            RenderMode::Normals => return (hit.normal + Vector3::new(1.0, 1.0, 1.0)) * 0.5,
+            RenderMode::Depth { near, far } => {
+                let range = (far - near).max(f64::EPSILON);
+                let v = 1.0 - ((hit.t - near) / range).clamp(0.0, 1.0);
+                return Vector3::new(v, v, v);
+            }
+            RenderMode::Albedo => return hit.material.color_at(hit.u, hit.v),
+        }
+        let HitRecord {
+            point,
+            normal: n,
+            ref material,
+            ..
+        } = hit;
+        let color = material.color_at(hit.u, hit.v);
+
+        // transparent materials split what they let through between reflection
+        // and refraction according to the Fresnel reflectance
+        let eta = hit.relative_index();
+        let kr = fresnel(&ray.direction, &n, eta);
+        let reflect_weight = material.albedo[2] + material.albedo[3] * kr;
+        let refract_weight = material.albedo[3] * (1.0 - kr);
+        // mirrors and glass branch at every hit, so they keep the low cap; the
+        // path tracer follows just one branch through dielectrics instead
+        let stochastic = self.path_tracing && material.dielectric;
+        let branch = |weight: f64| weight != 0.0 && depth <= MAX_DEPTH && !stochastic;
+
+        let reflect_color = if branch(reflect_weight) {
+            let reflect_dir = material.reflect_direction(&ray.direction, &n, rng);
+            let reflect_orig = if reflect_dir * n < 0.0 {
+                point - n * 1e-3
+            } else {
+                point + n * 1e-3
+            };
+            let tint = if material.metallic {
+                color
+            } else {
+                Vector3::new(1.0, 1.0, 1.0)
+            };
+            let reflected = self.trace_path(
+                &Ray::new(reflect_orig, reflect_dir).with_time(ray.time),
+                depth + 1,
+                throughput.componentwise(&tint) * reflect_weight,
+                rng,
+            );
+            tint.componentwise(&reflected)
+        } else {
+            Vector3::new_zero()
+        };
+
+        let refract_color = if branch(refract_weight) {
+            let refract_dir = refract(&ray.direction, &n, eta, 1.0).normalize();
+            let refract_orig = if refract_dir * n < 0.0 {
+                point - n * 1e-3
+            } else {
+                point + n * 1e-3
+            };
+            self.trace_path(
+                &Ray::new(refract_orig, refract_dir).with_time(ray.time),
+                depth + 1,
+                throughput * refract_weight,
+                rng,
+            )
+        } else {
+            Vector3::new_zero()
+        };
+
+        let bounce = self
+            .path_tracing
+            .then(|| material.scatter(ray, &hit, rng))
+            .flatten();
+        let bounce_color = match bounce {
+            Some((scattered, attenuation)) => {
+                // past the first few bounces, end paths that can no longer add
+                // much at random and make up for it by boosting the ones that
+                // go on
+                let throughput = throughput.componentwise(&attenuation);
+                let survival = if depth >= ROULETTE_DEPTH {
+                    throughput.max_component().clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+                if survival < 1.0 && rng.next_f64() >= survival {
+                    Vector3::new_zero()
+                } else {
+                    let incoming =
+                        self.trace_path(&scattered, depth + 1, throughput / survival, rng);
+                    attenuation.componentwise(&incoming) / survival
+                }
+            }
+            None => Vector3::new_zero(),
+        };
+
+        let mut diffuse_light_intensity = 0.0;
+        let mut specular_light_intensity = 0.0;
+        let enabled_lights = self.lights.iter().filter(|light| light.enabled);
+        for light in enabled_lights.map(|light| &light.item) {
+            let light_dir = light.direction_from(&point).0;
+            let visibility = self.visibility(&point, &n, light, ray.time, rng);
+            if visibility == 0.0 {
+                continue;
+            }
+
+            let intensity = light.intensity_at(&point) * visibility;
+            diffuse_light_intensity += intensity * f64::max(0.0, light_dir * n);
+            specular_light_intensity += f64::max(0.0, -reflect(&-light_dir, &n) * ray.direction)
+                .powf(material.specular_exponent)
+                * intensity;
+        }
+
+        let ambient = if self.ambient == Vector3::new_zero() {
+            self.ambient // nothing to occlude, so skip the rays
+        } else {
+            self.ambient * self.ambient_occlusion(&point, &n, ray.time, rng)
+        };
+
+        material.emission
+            + color.componentwise(&ambient)
+            + color * diffuse_light_intensity * material.albedo[0]
+            + Vector3::new(1.0, 1.0, 1.0) * specular_light_intensity * material.albedo[1]
+            + reflect_color * reflect_weight
+            + refract_color * refract_weight
+            + bounce_color
+    }
+
+    /// Fraction of the ambient light reaching `point`, on a surface facing
+    /// `n`: 1 when nothing lies within `ao_radius` above it at `time`.
+    fn ambient_occlusion(&self, point: &Vector3, n: &Vector3, time: f64, rng: &mut Rng) -> f64 {
+        if self.ambient_occlusion_samples == 0 {
+            return 1.0;
+        }
+        let origin = *point + *n * 1e-3;
+        let occluded = (0..self.ambient_occlusion_samples)
+            .filter(|_| {
+                let direction = Vector3::random_in_hemisphere(n, rng);
+                let ray = Ray::new(origin, direction).with_time(time);
+                self.intersect(&ray, self.ao_radius.min(self.max_distance))
+                    .is_some()
+            })
+            .count();
+        1.0 - occluded as f64 / self.ambient_occlusion_samples as f64
+    }
+
+    /// Fraction of `light` that `point`, on a surface facing `n`, can see:
+    /// either 0 or 1 for lights without size, or the share of unblocked
+    /// shadow rays towards a spherical one. Moving objects cast their
+    /// shadows from where they are at `time`.
+    fn visibility(
+        &self,
+        point: &Vector3,
+        n: &Vector3,
+        light: &Light,
+        time: f64,
+        rng: &mut Rng,
+    ) -> f64 {
+        let samples = if light.radius() > 0.0 {
+            self.shadow_samples.max(1)
+        } else {
+            1
+        };
+        let unblocked = (0..samples)
+            .filter(|_| {
+                let (light_dir, light_distance) = light.sample_direction_from(point, rng);
+                // offset the origin so the shadow ray doesn't hit the surface it starts on
+                let shadow_orig = if light_dir * *n < 0.0 {
+                    *point - *n * 1e-3
+                } else {
+                    *point + *n * 1e-3
+                };
+                self.intersect(
+                    &Ray::new(shadow_orig, light_dir).with_time(time),
+                    self.max_distance,
+                )
+                .is_none_or(|shadow_hit| {
+                    shadow_hit.point.distance_squared(&shadow_orig)
+                        >= light_distance * light_distance
+                })
+            })
+            .count();
+        unblocked as f64 / samples as f64
+    }
+}
+
+/// A scene description that can be read from and written to JSON with the
+/// `serde` feature. Entries marked `"enabled": false` are kept but left out
+/// of renders.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SceneDescription {
+    pub spheres: Vec<Toggle<Sphere>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub planes: Vec<Toggle<Plane>>,
+    pub lights: Vec<Toggle<Light>>,
+    #[cfg_attr(feature = "serde", serde(default = "Vector3::new_zero"))]
+    pub ambient: Vector3,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub path_tracing: bool,
+    #[cfg_attr(feature = "serde", serde(default = "default_shadow_samples"))]
+    pub shadow_samples: usize,
+    /// Far limit of the view; unlimited when left out.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_distance: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ambient_occlusion_samples: usize,
+    #[cfg_attr(feature = "serde", serde(default = "default_ao_radius"))]
+    pub ao_radius: f64,
+}
+
+#[cfg(feature = "serde")]
+fn default_shadow_samples() -> usize {
+    DEFAULT_SHADOW_SAMPLES
+}
+
+#[cfg(feature = "serde")]
+fn default_ao_radius() -> f64 {
+    DEFAULT_AO_RADIUS
+}
+
+impl SceneDescription {
+    /// Boxes copies of every object for tracing, each switched on or off as
+    /// described.
+    pub fn objects(&self) -> Vec<Toggle<Box<dyn Hittable>>> {
+        let spheres = self.spheres.iter().map(|sphere| Toggle {
+            item: Box::new(sphere.item.clone()) as Box<dyn Hittable>,
+            enabled: sphere.enabled,
+        });
+        let planes = self.planes.iter().map(|plane| Toggle {
+            item: Box::new(plane.item.clone()) as Box<dyn Hittable>,
+            enabled: plane.enabled,
+        });
+        spheres.chain(planes).collect()
+    }
+
+    /// Builds a renderable scene from copies of the described objects.
+    pub fn to_scene(&self) -> Scene {
+        Scene {
+            ambient: self.ambient,
+            path_tracing: self.path_tracing,
+            shadow_samples: self.shadow_samples,
+            max_distance: self.max_distance.unwrap_or(f64::INFINITY),
+            ambient_occlusion_samples: self.ambient_occlusion_samples,
+            ao_radius: self.ao_radius,
+            objects: self.objects(),
+            lights: self.lights.clone(),
+            ..Scene::new(vec![], vec![])
+        }
+    }
+}
+
+impl Default for SceneDescription {
+    /// Four spheres over a checkerboard floor, lit by two lights.
+    fn default() -> SceneDescription {
+        SceneDescription {
+            spheres: vec![
+                Sphere::new(Vector3::new(-3.0, 0.0, -16.0), 2.0, Material::ivory()).into(),
+                Sphere::new(Vector3::new(-1.0, -1.5, -12.0), 2.0, Material::glass()).into(),
+                Sphere::new(Vector3::new(1.5, -0.5, -18.0), 3.0, Material::red_rubber()).into(),
+                Sphere::new(Vector3::new(7.0, 5.0, -18.0), 4.0, Material::mirror()).into(),
+            ],
+            planes: vec![Plane::checkerboard(
+                Vector3::new(0.0, -4.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                Material::new(Vector3::new(0.3, 0.3, 0.3)),
+                Vector3::new(0.3, 0.2, 0.1),
+            )
+            .into()],
+            lights: vec![
+                Light::new(Vector3::new(-20.0, 20.0, 20.0), 1.5).into(),
+                Light::new(Vector3::new(30.0, 50.0, -25.0), 1.8).into(),
+            ],
+            ambient: Vector3::new_zero(),
+            path_tracing: false,
+            shadow_samples: DEFAULT_SHADOW_SAMPLES,
+            max_distance: None,
+            ambient_occlusion_samples: 0,
+            ao_radius: DEFAULT_AO_RADIUS,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "could not read scene: {}", err),
+            SceneError::Parse(err) => write!(f, "invalid scene: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(err) => Some(err),
+            SceneError::Parse(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for SceneError {
+    fn from(err: io::Error) -> SceneError {
+        SceneError::Io(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for SceneError {
+    fn from(err: serde_json::Error) -> SceneError {
+        SceneError::Parse(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+/// Reads a JSON scene description.
+pub fn load_scene(path: &str) -> Result<SceneDescription, SceneError> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{background, EnvironmentMap, BACKGROUND_BOTTOM, BACKGROUND_TOP};
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn scene_round_trips_through_json_file() {
+        let path = std::env::temp_dir().join("tiny_rusty_raytracer_scene.json");
+        let path = path.to_str().unwrap();
+        let scene = SceneDescription::default();
+
+        fs::write(path, serde_json::to_string_pretty(&scene).unwrap()).unwrap();
+        let loaded = load_scene(path);
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded.unwrap(), scene);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn materials_only_need_a_color() {
+        let json = r#"{
+            "spheres": [{
+                "center": {"x": 0.0, "y": 0.0, "z": -5.0},
+                "radius": 1.0,
+                "material": {"color": {"x": 1.0, "y": 0.0, "z": 0.0}}
+            }],
+            "lights": [{"position": {"x": 0.0, "y": 5.0, "z": 0.0}, "intensity": 1.0}]
+        }"#;
+        let scene: SceneDescription = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            scene.spheres[0].item,
+            Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                Material::new(Vector3::new(1.0, 0.0, 0.0))
+            )
+        );
+        assert!(scene.planes.is_empty());
+        assert!(scene.spheres[0].enabled);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn switched_off_entries_round_trip_through_json() {
+        let mut description = SceneDescription::default();
+        description.spheres[1].enabled = false;
+        description.lights[0].enabled = false;
+        let json = serde_json::to_string(&description).unwrap();
+        let loaded: SceneDescription = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, description);
+
+        let scene = loaded.to_scene();
+        assert!(!scene.objects[1].enabled);
+        assert!(!scene.lights[0].enabled);
+        assert!(scene.objects[0].enabled && scene.lights[1].enabled);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn lights_are_told_apart_by_their_fields() {
+        let json = r#"[
+            {"position": {"x": 0.0, "y": 5.0, "z": 0.0}, "intensity": 1.0},
+            {"direction": {"x": 0.0, "y": -1.0, "z": 0.0}, "intensity": 2.0},
+            {
+                "position": {"x": 0.0, "y": 5.0, "z": 0.0},
+                "direction": {"x": 0.0, "y": -1.0, "z": 0.0},
+                "intensity": 3.0,
+                "inner_angle": 0.2,
+                "outer_angle": 0.4
+            }
+        ]"#;
+        let lights: Vec<Light> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            lights,
+            vec![
+                Light::new(Vector3::new(0.0, 5.0, 0.0), 1.0),
+                Light::directional(Vector3::new(0.0, -1.0, 0.0), 2.0),
+                Light::spot(
+                    Vector3::new(0.0, 5.0, 0.0),
+                    Vector3::new(0.0, -1.0, 0.0),
+                    3.0,
+                    0.2,
+                    0.4
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_scene_is_never_hit() {
+        let scene = Scene::new(vec![], vec![]);
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert!(scene.intersect(&ray).is_none());
+    }
+
+    #[test]
+    fn trace_matches_cast_ray() {
+        let description = SceneDescription::default();
+        let scene = description.to_scene();
+        let objects: Vec<_> = description.objects().into_iter().map(|o| o.item).collect();
+        let lights: Vec<_> = description.lights.iter().map(|l| l.item).collect();
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(-0.1, -0.1, -1.0));
+        assert_eq!(
+            scene.trace(&ray, 0, &mut Rng::new(0)),
+            crate::cast_ray(&ray, &objects, &lights, 0)
+        );
+    }
+
+    #[test]
+    fn disabled_entries_are_left_out() {
+        let mut scene = Scene::new(
+            vec![Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                Material::new(Vector3::new(1.0, 0.5, 0.5)),
+            ))],
+            vec![Light::new(Vector3::new(0.0, 0.0, 20.0), 1.0)],
+        );
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let lit = scene.trace(&ray, 0, &mut Rng::new(0));
+
+        scene.lights[0].enabled = false;
+        let unlit = scene.trace(&ray, 0, &mut Rng::new(0));
+        assert!(unlit.norm() < lit.norm());
+
+        scene.objects[0].enabled = false;
+        assert!(scene.intersect(&ray).is_none());
+        assert_eq!(
+            scene.trace(&ray, 0, &mut Rng::new(0)),
+            background(&ray.direction, BACKGROUND_TOP, BACKGROUND_BOTTOM)
+        );
+
+        // the flag stays with its entry when others are added in front
+        let behind = Plane::new(
+            Vector3::new(0.0, 0.0, 5.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Material::default(),
+        );
+        scene.objects.insert(0, Toggle::new(Box::new(behind)));
+        assert!(scene.intersect(&ray).is_none());
+    }
+
+    #[test]
+    fn ambient_occlusion_darkens_only_near_geometry() {
+        let sphere = Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, Material::default());
+        let mut scene = Scene::new(vec![Box::new(sphere)], vec![]);
+        scene.ambient_occlusion_samples = 64;
+        let mut rng = Rng::new(1);
+        for _ in 0..50 {
+            let n = Vector3::random_unit(&mut rng);
+            let point = Vector3::new(0.0, 0.0, -5.0) + n;
+            assert_eq!(
+                scene.tracer().ambient_occlusion(&point, &n, 0.0, &mut rng),
+                1.0
+            );
+        }
+
+        // a floor touching the sphere from below
+        scene.objects.push(Toggle::new(Box::new(Plane::new(
+            Vector3::new(0.0, -1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Material::default(),
+        ))));
+        let crease = Vector3::new(0.5, -1.0, -5.0);
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        assert!(
+            scene
+                .tracer()
+                .ambient_occlusion(&crease, &up, 0.0, &mut rng)
+                < 1.0
+        );
+    }
+
+    #[test]
+    fn debug_modes_show_normals_and_albedo() {
+        let sphere = Sphere::new(
+            Vector3::new(-2.0, 0.0, 0.0),
+            1.0,
+            Material::new(Vector3::new(0.3, 0.6, 0.9)),
+        );
+        let mut scene = Scene::new(
+            vec![Box::new(sphere)],
+            vec![Light::new(Vector3::new(0.0, 5.0, 0.0), 1.0)],
+        );
+        // towards the sphere's +X pole
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(-1.0, 0.0, 0.0));
+
+        scene.render_mode = RenderMode::Normals;
+        let normal_color = scene.trace(&ray, 0, &mut Rng::new(0));
+        assert!(normal_color.approx_eq(&Vector3::new(1.0, 0.5, 0.5), 1e-9));
+
+        scene.render_mode = RenderMode::Albedo;
+        assert_eq!(
+            scene.trace(&ray, 0, &mut Rng::new(0)),
+            Vector3::new(0.3, 0.6, 0.9)
+        );
+
+        scene.render_mode = RenderMode::Depth {
+            near: 0.0,
+            far: 4.0,
+        };
+        assert_eq!(
+            scene.trace(&ray, 0, &mut Rng::new(0)),
+            Vector3::new(0.75, 0.75, 0.75)
+        );
+        let miss = Ray::new(Vector3::new_zero(), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(scene.trace(&miss, 0, &mut Rng::new(0)), Vector3::new_zero());
+    }
+
+    #[test]
+    fn closer_hits_are_brighter_in_depth_mode() {
+        let depth_along = |z: f64| {
+            let sphere = Sphere::new(Vector3::new(0.0, 0.0, z), 1.0, Material::default());
+            let mut scene = Scene::new(vec![Box::new(sphere)], vec![]);
+            scene.render_mode = RenderMode::Depth {
+                near: 1.0,
+                far: 20.0,
+            };
+            let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+            scene.trace(&ray, 0, &mut Rng::new(0)).x
+        };
+        assert!(depth_along(-5.0) > depth_along(-10.0));
+        assert_eq!(depth_along(-1.5), 1.0);
+        assert_eq!(depth_along(-30.0), 0.0);
+    }
+
+    #[test]
+    fn empty_depth_range_splits_into_white_and_black() {
+        let sphere = Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, Material::default());
+        let mut scene = Scene::new(vec![Box::new(sphere)], vec![]);
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let white = Vector3::new(1.0, 1.0, 1.0);
+        // the hit is 4 away
+        for (near, far, expected) in [
+            (4.0, 4.0, white),
+            (5.0, 5.0, white),
+            (3.0, 3.0, Vector3::new_zero()),
+            (10.0, 2.0, white),
+            (3.0, 1.0, Vector3::new_zero()),
+        ] {
+            scene.render_mode = RenderMode::Depth { near, far };
+            assert_eq!(scene.trace(&ray, 0, &mut Rng::new(0)), expected);
+        }
+    }
+
+    #[test]
+    fn path_traced_glowing_room_converges_to_its_energy() {
+        // every wall emits 0.5 and reflects half of what reaches it, so all
+        // bounces together add up to 0.5 / (1 - 0.5)
+        let walls = Material {
+            albedo: [0.5, 0.0, 0.0, 0.0],
+            emission: Vector3::new(0.5, 0.5, 0.5),
+            ..Material::new(Vector3::new(1.0, 1.0, 1.0))
+        };
+        // a cube of planes facing inwards
+        let axes = [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ];
+        let objects = axes
+            .iter()
+            .flat_map(|&axis| [axis, -axis])
+            .map(|normal| {
+                Box::new(Plane::new(normal * -5.0, normal, walls.clone())) as Box<dyn Hittable>
+            })
+            .collect();
+        let mut scene = Scene::new(objects, vec![]);
+        scene.path_tracing = true;
+
+        let mut rng = Rng::new(3);
+        let samples = 4000;
+        let total = (0..samples).fold(Vector3::new_zero(), |total, _| {
+            let ray = Ray::new(Vector3::new_zero(), Vector3::random_unit(&mut rng));
+            total + scene.trace(&ray, 0, &mut rng)
+        });
+        let mean = total / samples as f64;
+        assert!(
+            mean.approx_eq(&Vector3::new(1.0, 1.0, 1.0), 0.05),
+            "{:?}",
+            mean
+        );
+    }
+
+    #[test]
+    fn roulette_ends_paths_by_their_whole_throughput() {
+        // a white surface passes on all light it gets, but paths already
+        // this dim are ended at it all the same
+        let white = Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        );
+        let mut scene = Scene::new(vec![Box::new(white)], vec![]);
+        scene.path_tracing = true;
+        let tracer = scene.tracer();
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let mut rng = Rng::new(0);
+        let mut ended = |throughput: f64| {
+            let throughput = Vector3::new(throughput, throughput, throughput);
+            (0..100)
+                .filter(|_| {
+                    tracer.trace_path(&ray, ROULETTE_DEPTH, throughput, &mut rng)
+                        == Vector3::new_zero()
+                })
+                .count()
+        };
+        assert_eq!(ended(1e-6), 100);
+        assert_eq!(ended(1.0), 0);
+    }
+
+    #[test]
+    fn ambient_light_reaches_unlit_surfaces() {
+        let mut scene = Scene::new(
+            vec![Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                Material::new(Vector3::new(1.0, 0.5, 0.5)),
+            ))],
+            vec![Light::new(Vector3::new(0.0, 0.0, -20.0), 1.0)],
+        );
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(scene.trace(&ray, 0, &mut Rng::new(0)), Vector3::new_zero());
+
+        scene.ambient = Vector3::new(0.2, 0.2, 0.2);
+        assert_eq!(
+            scene.trace(&ray, 0, &mut Rng::new(0)),
+            Vector3::new(0.2, 0.1, 0.1)
+        );
+    }
+
+    #[test]
+    fn path_tracing_gathers_light_from_emissive_surroundings() {
+        let sky = Material {
+            emission: Vector3::new(1.0, 1.0, 1.0),
+            ..Material::new(Vector3::new_zero())
+        };
+        let mut scene = Scene::new(
+            vec![
+                Box::new(Sphere::new(
+                    Vector3::new(0.0, 0.0, -5.0),
+                    1.0,
+                    Material::new(Vector3::new(0.5, 0.5, 0.5)),
+                )),
+                Box::new(Sphere::new(Vector3::new_zero(), 50.0, sky)),
+            ],
+            vec![],
+        );
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let mut rng = Rng::new(0);
+        assert_eq!(scene.trace(&ray, 0, &mut rng), Vector3::new_zero());
+
+        // every bounce off the gray sphere lands on the glowing enclosure
+        scene.path_tracing = true;
+        let color = scene.trace(&ray, 0, &mut rng);
+        assert!(color.approx_eq(&Vector3::new(0.5, 0.5, 0.5), 1e-9));
+    }
+
+    fn shadowed_scene(light: Light) -> Scene {
+        Scene::new(
+            vec![
+                Box::new(Sphere::new(
+                    Vector3::new(0.0, 0.0, -5.0),
+                    1.0,
+                    Material::new(Vector3::new(1.0, 1.0, 1.0)),
+                )),
+                Box::new(Sphere::new(
+                    Vector3::new(0.0, 5.0, -4.0),
+                    1.0,
+                    Material::new(Vector3::new(1.0, 1.0, 1.0)),
+                )),
+            ],
+            vec![light],
+        )
+    }
+
+    #[test]
+    fn light_without_radius_casts_hard_shadows() {
+        let position = Vector3::new(0.0, 10.0, -4.0);
+        let hard = shadowed_scene(Light::new(position, 1.0));
+        let soft = shadowed_scene(Light::spherical(position, 1.0, 0.0));
+        for x in [0.0, 0.2, 0.4, 0.6] {
+            let ray = Ray::new(Vector3::new(x, 0.5, 0.0), Vector3::new(0.0, 0.0, -1.0));
+            assert_eq!(
+                soft.trace(&ray, 0, &mut Rng::new(1)),
+                hard.trace(&ray, 0, &mut Rng::new(1))
+            );
+        }
+    }
+
+    #[test]
+    fn spherical_light_casts_penumbra() {
+        let position = Vector3::new(0.0, 10.0, -4.0);
+        let ray = Ray::new(Vector3::new(0.0, 0.5, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        let mut unblocked = shadowed_scene(Light::new(position, 1.0));
+        unblocked.objects.truncate(1);
+        let lit = unblocked.trace(&ray, 0, &mut Rng::new(1));
+        let hard = shadowed_scene(Light::new(position, 1.0)).trace(&ray, 0, &mut Rng::new(1));
+        let soft =
+            shadowed_scene(Light::spherical(position, 1.0, 3.0)).trace(&ray, 0, &mut Rng::new(1));
+        assert!(hard.x < soft.x && soft.x < lit.x);
+    }
+
+    #[test]
+    fn missed_rays_sample_the_environment_map() {
+        let mut scene = Scene::new(vec![], vec![]);
+        scene.background = Background::Environment(
+            EnvironmentMap::new(1, 1, vec![Vector3::new(0.1, 0.2, 0.3)]).unwrap(),
+        );
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(
+            scene.trace(&ray, 0, &mut Rng::new(0)),
+            Vector3::new(0.1, 0.2, 0.3)
+        );
+    }
+
+    #[test]
+    fn scenes_build_up_in_a_chain() {
+        let scene = Scene::new(vec![], vec![])
+            .add_object(Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                Material::default(),
+            ))
+            .add_object(Plane::new(
+                Vector3::new(0.0, -1.0, 0.0),
+                Vector3::new(0.0, 1.0, 0.0),
+                Material::default(),
+            ))
+            .add_light(Light::new(Vector3::new(0.0, 5.0, 0.0), 1.0))
+            .with_background(Background::Solid(Vector3::new(1.0, 0.0, 0.0)))
+            .with_ambient(Vector3::new(0.1, 0.1, 0.1));
+
+        assert_eq!(scene.objects.len(), 2);
+        assert_eq!(scene.lights.len(), 1);
+        assert_eq!(
+            scene.background,
+            Background::Solid(Vector3::new(1.0, 0.0, 0.0))
+        );
+        assert_eq!(scene.ambient, Vector3::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn picking_reports_the_object_under_a_pixel() {
+        let sphere = |x| Sphere::new(Vector3::new(x, 0.0, -5.0), 1.0, Material::default());
+        let mut scene = Scene::new(vec![], vec![])
+            .add_object(sphere(-3.0))
+            .add_object(sphere(0.0))
+            .add_object(sphere(3.0));
+
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(scene.intersect(&ray).unwrap().object_id, Some(1));
+
+        let camera = Camera::new(Vector3::new_zero(), std::f64::consts::PI / 2.0, 101, 101);
+        assert_eq!(scene.pick(&camera, 50, 50).unwrap().object_id, Some(1));
+        assert_eq!(scene.pick(&camera, 50, 0), None);
+
+        // ids stay those of the full list with objects switched off
+        scene.objects[0].enabled = false;
+        assert_eq!(scene.intersect(&ray).unwrap().object_id, Some(1));
+    }
+
+    #[test]
+    fn missed_rays_see_a_solid_background() {
+        let mut scene = Scene::new(
+            vec![Box::new(Sphere::new(
+                Vector3::new(0.0, 0.0, -5.0),
+                1.0,
+                Material::default(),
+            ))],
+            vec![],
+        );
+        let red = Vector3::new(1.0, 0.0, 0.0);
+        scene.background = Background::Solid(red);
+        let mut rng = Rng::new(0);
+        for _ in 0..100 {
+            let ray = Ray::new(Vector3::new_zero(), Vector3::random_unit(&mut rng));
+            if scene.intersect(&ray).is_none() {
+                assert_eq!(scene.trace(&ray, 0, &mut rng), red);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn missing_file_is_an_io_error() {
+        assert!(matches!(
+            load_scene("/nonexistent-directory/scene.json"),
+            Err(SceneError::Io(_))
+        ));
+    }
+}
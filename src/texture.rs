@@ -0,0 +1,245 @@
+use std::io;
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{EnvironmentMap, Vector3};
+
+/// Color varying over a surface, looked up by texture coordinates (u, v),
+/// both in [0, 1] with v = 0 along the top edge. Coordinates outside that
+/// range wrap around, so images repeat. In JSON a plain color is a solid
+/// texture, and images whose pixels don't fill `width` by `height` are
+/// rejected.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged, try_from = "TextureFields"))]
+pub enum Texture {
+    SolidColor(Vector3),
+    /// Squares alternating between `even` and `odd`, `scale` of them to a
+    /// unit of texture space along each direction.
+    Checker {
+        even: Vector3,
+        odd: Vector3,
+        scale: f64,
+    },
+    /// Linear-color pixels, row by row from the top-left corner, shared
+    /// between the materials using them.
+    Image {
+        width: usize,
+        height: usize,
+        pixels: Arc<[Vector3]>,
+        #[cfg_attr(feature = "serde", serde(default))]
+        filter: Filter,
+    },
+}
+
+/// How an image texture is sampled between pixel centers.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Filter {
+    /// The color of the pixel the coordinates fall in; sharp, but blocky
+    /// when magnified.
+    #[default]
+    Nearest,
+    /// A blend of the four pixels around the coordinates.
+    Bilinear,
+}
+
+impl Texture {
+    /// Reads an image texture, undoing its gamma so the pixels are linear.
+    pub fn load_image(path: &str) -> io::Result<Texture> {
+        let image = EnvironmentMap::load(path)?;
+        Ok(Texture::Image {
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_pixels().into(),
+            filter: Filter::default(),
+        })
+    }
+
+    pub fn sample(&self, u: f64, v: f64) -> Vector3 {
+        match self {
+            Texture::SolidColor(color) => *color,
+            Texture::Checker { even, odd, scale } => {
+                if ((u * scale).floor() + (v * scale).floor()) as i64 % 2 == 0 {
+                    *even
+                } else {
+                    *odd
+                }
+            }
+            Texture::Image {
+                width,
+                height,
+                pixels,
+                filter: Filter::Nearest,
+            } => {
+                let x = ((u.rem_euclid(1.0) * *width as f64) as usize).min(width - 1);
+                let y = ((v.rem_euclid(1.0) * *height as f64) as usize).min(height - 1);
+                pixels[y * width + x]
+            }
+            Texture::Image {
+                width,
+                height,
+                pixels,
+                filter: Filter::Bilinear,
+            } => {
+                let (x0, x1, tx) = texel_span(u, *width);
+                let (y0, y1, ty) = texel_span(v, *height);
+                Vector3::bilerp(
+                    &pixels[y0 * width + x0],
+                    &pixels[y0 * width + x1],
+                    &pixels[y1 * width + x0],
+                    &pixels[y1 * width + x1],
+                    tx,
+                    ty,
+                )
+            }
+        }
+    }
+}
+
+/// The two pixels, out of `size`, whose centers surround coordinate `t` and
+/// how far `t` is from the first towards the second. Beyond the outer
+/// centers the span wraps around to the pixel on the opposite edge.
+fn texel_span(t: f64, size: usize) -> (usize, usize, f64) {
+    let position = t.rem_euclid(1.0) * size as f64 - 0.5;
+    let floor = position.floor();
+    let first = floor.rem_euclid(size as f64) as usize;
+    (first, (first + 1) % size, position - floor)
+}
+
+/// A [`Texture`] as read from JSON, before its image is checked.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TextureFields {
+    SolidColor(Vector3),
+    Checker {
+        even: Vector3,
+        odd: Vector3,
+        scale: f64,
+    },
+    Image {
+        width: usize,
+        height: usize,
+        pixels: Arc<[Vector3]>,
+        #[serde(default)]
+        filter: Filter,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TextureFields> for Texture {
+    type Error = String;
+
+    fn try_from(fields: TextureFields) -> Result<Texture, String> {
+        Ok(match fields {
+            TextureFields::SolidColor(color) => Texture::SolidColor(color),
+            TextureFields::Checker { even, odd, scale } => Texture::Checker { even, odd, scale },
+            TextureFields::Image {
+                width,
+                height,
+                pixels,
+                filter,
+            } => {
+                if width == 0 || height == 0 || pixels.len() != width * height {
+                    return Err(format!(
+                        "image texture of {}x{} pixels holds {}",
+                        width,
+                        height,
+                        pixels.len()
+                    ));
+                }
+                Texture::Image {
+                    width,
+                    height,
+                    pixels,
+                    filter,
+                }
+            }
+        })
+    }
+}
+
+impl From<Vector3> for Texture {
+    fn from(color: Vector3) -> Texture {
+        Texture::SolidColor(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checker_alternates_between_adjacent_cells() {
+        let white = Vector3::new(1.0, 1.0, 1.0);
+        let black = Vector3::new_zero();
+        let checker = Texture::Checker {
+            even: white,
+            odd: black,
+            scale: 4.0,
+        };
+        assert_eq!(checker.sample(0.1, 0.1), white);
+        assert_eq!(checker.sample(0.35, 0.1), black);
+        assert_eq!(checker.sample(0.1, 0.35), black);
+        assert_eq!(checker.sample(0.35, 0.35), white);
+    }
+
+    #[test]
+    fn image_is_sampled_by_pixel() {
+        let pixels: Vec<Vector3> = (0..6).map(|i| Vector3::new(i as f64, 0.0, 0.0)).collect();
+        let image = Texture::Image {
+            width: 3,
+            height: 2,
+            pixels: pixels.into(),
+            filter: Filter::Nearest,
+        };
+        assert_eq!(image.sample(0.0, 0.0).x, 0.0);
+        assert_eq!(image.sample(0.5, 0.75).x, 4.0);
+        assert_eq!(image.sample(0.99, 0.99).x, 5.0);
+        // outside [0, 1] the image repeats
+        assert_eq!(image.sample(1.0, 1.0).x, 0.0);
+        assert_eq!(image.sample(1.5, -0.25).x, 4.0);
+    }
+
+    #[test]
+    fn bilinear_blends_neighboring_pixels() {
+        let red = Vector3::new(1.0, 0.0, 0.0);
+        let blue = Vector3::new(0.0, 0.0, 1.0);
+        let image = Texture::Image {
+            width: 2,
+            height: 1,
+            pixels: vec![red, blue].into(),
+            filter: Filter::Bilinear,
+        };
+        assert_eq!(image.sample(0.5, 0.5), Vector3::new(0.5, 0.0, 0.5));
+        assert_eq!(image.sample(0.25, 0.5), red);
+        assert_eq!(image.sample(0.75, 0.0), blue);
+        // past the outer centers the blend wraps to the opposite edge
+        assert_eq!(image.sample(1.0, 0.0), Vector3::new(0.5, 0.0, 0.5));
+        assert_eq!(image.sample(0.0, 0.0), image.sample(1.0, 0.0));
+        assert_eq!(image.sample(-0.25, 0.0), blue);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn images_must_fill_their_size() {
+        let image = Texture::Image {
+            width: 2,
+            height: 1,
+            pixels: vec![Vector3::new_zero(), Vector3::new(1.0, 1.0, 1.0)].into(),
+            filter: Filter::Bilinear,
+        };
+        let json = serde_json::to_string(&image).unwrap();
+        assert_eq!(serde_json::from_str::<Texture>(&json).unwrap(), image);
+
+        for json in [
+            r#"{"width": 3, "height": 1, "pixels": [{"x": 0.0, "y": 0.0, "z": 0.0}]}"#,
+            r#"{"width": 0, "height": 0, "pixels": []}"#,
+        ] {
+            assert!(serde_json::from_str::<Texture>(json).is_err());
+        }
+    }
+}
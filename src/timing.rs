@@ -0,0 +1,36 @@
+use std::time::{Duration, Instant};
+
+/// Measures how long a stage of rendering takes.
+#[derive(Debug, Clone, Copy)]
+pub struct Timer {
+    start: Instant,
+}
+
+impl Timer {
+    pub fn start() -> Timer {
+        Timer {
+            start: Instant::now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn elapsed_ms(&self) -> f64 {
+        self.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_time_slept() {
+        let timer = Timer::start();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(timer.elapsed() >= Duration::from_millis(5));
+        assert!(timer.elapsed_ms() >= 5.0);
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Aabb, HitRecord, Hittable, Ray};
+
+/// An entry of a scene together with whether it is switched on, so that it
+/// can be left out of a render without being removed. The flag travels with
+/// the entry, whatever else is added, removed or reordered around it.
+///
+/// In scene files the entry's own fields are written directly, plus
+/// `"enabled": false` when it is switched off.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Toggle<T> {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub item: T,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "enabled", skip_serializing_if = "is_enabled")
+    )]
+    pub enabled: bool,
+}
+
+impl<T> Toggle<T> {
+    /// `item`, switched on.
+    pub fn new(item: T) -> Toggle<T> {
+        Toggle {
+            item,
+            enabled: true,
+        }
+    }
+}
+
+impl<T> From<T> for Toggle<T> {
+    fn from(item: T) -> Toggle<T> {
+        Toggle::new(item)
+    }
+}
+
+/// A switched-off object is never hit.
+impl<T: Hittable> Hittable for Toggle<T> {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        self.enabled.then(|| self.item.ray_intersect(ray)).flatten()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.item.bounding_box()
+    }
+}
+
+#[cfg(feature = "serde")]
+fn enabled() -> bool {
+    true
+}
+
+#[cfg(feature = "serde")]
+fn is_enabled(enabled: &bool) -> bool {
+    *enabled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Material, Sphere, Vector3};
+
+    #[test]
+    fn switched_off_objects_are_not_hit() {
+        let mut sphere = Toggle::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -5.0),
+            1.0,
+            Material::ivory(),
+        ));
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        assert!(sphere.ray_intersect(&ray).is_some());
+
+        sphere.enabled = false;
+        assert!(sphere.ray_intersect(&ray).is_none());
+        assert!(sphere.bounding_box().is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn only_switched_off_entries_write_the_flag() {
+        let sphere = Sphere::new(Vector3::new(0.0, 0.0, -5.0), 1.0, Material::ivory());
+        let on = serde_json::to_value(Toggle::new(sphere.clone())).unwrap();
+        assert_eq!(on, serde_json::to_value(&sphere).unwrap());
+
+        let off = Toggle {
+            item: sphere,
+            enabled: false,
+        };
+        let json = serde_json::to_string(&off).unwrap();
+        assert!(json.contains(r#""enabled":false"#));
+        assert_eq!(serde_json::from_str::<Toggle<Sphere>>(&json).unwrap(), off);
+    }
+}
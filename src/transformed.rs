@@ -0,0 +1,98 @@
+use crate::{Aabb, HitRecord, Hittable, Matrix4, Ray, Vector3};
+
+/// An object placed in the world by an affine transform, so one shape can be
+/// reused at many positions and orientations.
+pub struct Transformed<T: Hittable> {
+    pub object: T,
+    transform: Matrix4,
+    inverse: Matrix4,
+}
+
+impl<T: Hittable> Transformed<T> {
+    /// Panics if `transform` is singular, such as a scale by zero.
+    pub fn new(object: T, transform: Matrix4) -> Transformed<T> {
+        Transformed {
+            object,
+            transform,
+            inverse: transform.inverse().expect("transform must be invertible"),
+        }
+    }
+}
+
+impl<T: Hittable> Hittable for Transformed<T> {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        let local = Ray::new(
+            self.inverse.transform_point(&ray.origin),
+            self.inverse.transform_vector(&ray.direction),
+        )
+        .with_time(ray.time);
+        let hit = self.object.ray_intersect(&local)?;
+        // distances change under scaling, so measure t again in world space
+        let point = self.transform.transform_point(&hit.point);
+        let normal = self
+            .inverse
+            .transpose()
+            .transform_vector(&hit.normal)
+            .normalize();
+        Some(HitRecord {
+            t: point.distance(&ray.origin),
+            point,
+            normal,
+            ..hit
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox = self.object.bounding_box()?;
+        let corners = (0..8).map(|i| {
+            let pick = |bit: usize, min: f64, max: f64| if i & bit == 0 { min } else { max };
+            Vector3::new(
+                pick(1, bbox.min.x, bbox.max.x),
+                pick(2, bbox.min.y, bbox.max.y),
+                pick(4, bbox.min.z, bbox.max.z),
+            )
+        });
+        corners
+            .map(|corner| {
+                let p = self.transform.transform_point(&corner);
+                Aabb::new(p, p)
+            })
+            .reduce(|a, b| a.surrounding(&b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Material, Sphere};
+
+    fn unit_sphere() -> Sphere {
+        Sphere::new(Vector3::new_zero(), 1.0, Material::default())
+    }
+
+    #[test]
+    fn translated_sphere_is_hit_at_its_new_position() {
+        let sphere = Transformed::new(
+            unit_sphere(),
+            Matrix4::translation(Vector3::new(5.0, 0.0, 0.0)),
+        );
+        let ray = Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0));
+        let hit = sphere.ray_intersect(&ray).unwrap();
+        assert!((hit.t - 4.0).abs() < 1e-9);
+        assert!(hit.normal.approx_eq(&Vector3::new(-1.0, 0.0, 0.0), 1e-9));
+
+        let bbox = sphere.bounding_box().unwrap();
+        assert_eq!(bbox.min, Vector3::new(4.0, -1.0, -1.0));
+        assert_eq!(bbox.max, Vector3::new(6.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn scaled_sphere_keeps_world_distances_and_normals() {
+        let ellipsoid =
+            Transformed::new(unit_sphere(), Matrix4::scale(Vector3::new(2.0, 1.0, 1.0)));
+        let ray = Ray::new(Vector3::new(-5.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0));
+        let hit = ellipsoid.ray_intersect(&ray).unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-9);
+        assert!(hit.normal.approx_eq(&Vector3::new(-1.0, 0.0, 0.0), 1e-9));
+    }
+}
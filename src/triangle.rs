@@ -0,0 +1,111 @@
+use crate::{Aabb, HitRecord, Hittable, Material, Ray, Vector3};
+
+/// A triangle with vertices in counter-clockwise order when seen from the
+/// side its normal faces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    pub v0: Vector3,
+    pub v1: Vector3,
+    pub v2: Vector3,
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(v0: Vector3, v1: Vector3, v2: Vector3, material: Material) -> Triangle {
+        Triangle {
+            v0,
+            v1,
+            v2,
+            material,
+        }
+    }
+
+    pub fn normal(&self) -> Vector3 {
+        (self.v1 - self.v0).cross(&(self.v2 - self.v0)).normalize()
+    }
+}
+
+impl Hittable for Triangle {
+    fn ray_intersect(&self, ray: &Ray) -> Option<HitRecord> {
+        let (t, u, v) = intersect_triangle(ray, &self.v0, &self.v1, &self.v2)?;
+        let (front_face, normal) = HitRecord::face_normal(ray, self.normal());
+        Some(HitRecord {
+            t,
+            point: ray.at(t),
+            normal,
+            front_face,
+            object_id: None,
+            material: self.material.clone(),
+            u,
+            v,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.v0.min(&self.v1).min(&self.v2),
+            self.v0.max(&self.v1).max(&self.v2),
+        ))
+    }
+}
+
+/// Möller–Trumbore intersection, giving the distance along `ray` and the
+/// barycentric coordinates (u, v) of the hit.
+pub(crate) fn intersect_triangle(
+    ray: &Ray,
+    v0: &Vector3,
+    v1: &Vector3,
+    v2: &Vector3,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = *v1 - *v0;
+    let edge2 = *v2 - *v0;
+    let p = ray.direction.cross(&edge2);
+    let det = edge1 * p;
+    if det.abs() < 1e-12 {
+        return None; // the ray is parallel to the triangle
+    }
+    let inv_det = 1.0 / det;
+    let s = ray.origin - *v0;
+    let u = (s * p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(&edge1);
+    let v = (ray.direction * q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = (edge2 * q) * inv_det;
+    if t < 0.0 {
+        return None;
+    }
+    Some((t, u, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Vector3::new(-1.0, -1.0, -5.0),
+            Vector3::new(1.0, -1.0, -5.0),
+            Vector3::new(0.0, 1.0, -5.0),
+            Material::new(Vector3::new(1.0, 1.0, 1.0)),
+        )
+    }
+
+    #[test]
+    fn ray_hits_triangle_interior() {
+        let ray = Ray::new(Vector3::new_zero(), Vector3::new(0.0, 0.0, -1.0));
+        let hit = triangle().ray_intersect(&ray).unwrap();
+        assert_eq!(hit.t, 5.0);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn ray_misses_outside_edge() {
+        let ray = Ray::new(Vector3::new(1.0, 1.0, 0.0), Vector3::new(0.0, 0.0, -1.0));
+        assert_eq!(triangle().ray_intersect(&ray), None);
+    }
+}